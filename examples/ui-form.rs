@@ -0,0 +1,34 @@
+use rish::*;
+
+rish::ui! {
+    struct LoginForm {
+        name_label: TkLabel { text("Name:") } grid { row(0), column(0), sticky(Sticky::E) },
+        name: TkEntry {} grid { row(0), column(1), sticky(Sticky::EW), padx(5) },
+        volume: TkScale = make_scale(Orientation::Horizontal) {
+            from(0.0),
+            to(10.0),
+        } grid { row(1), column(1), sticky(Sticky::EW), padx(5) },
+        buttons: TkFrame {} grid { row(2), column(1), sticky(Sticky::E) },
+        cancel: TkButton in buttons {
+            text("Cancel"),
+            command(rish::end_wish),
+        } pack { side(PackSide::Left) },
+        ok: TkButton in buttons {
+            text("OK"),
+            command({
+                let name = name.clone();
+                move || println!("Hello {}", name.value_get())
+            }),
+        } pack { side(PackSide::Left), padx(5) },
+    }
+}
+
+fn main() {
+    let root = rish::start_wish().unwrap();
+
+    let form = LoginForm::new(&root);
+    root.grid_column(1).weight(1).configure();
+    form.name.focus();
+
+    rish::mainloop();
+}
@@ -28,6 +28,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -55,6 +56,7 @@ impl widget::TkWidget for TkButton {
 
 impl grid::TkGridLayout for TkButton {}
 impl pack::TkPackLayout for TkButton {}
+impl place::TkPlaceLayout for TkButton {}
 
 impl widget::TkLabelOptions for TkButton {}
 
@@ -1,19 +1,215 @@
 //! Canvas widget - displays (interactive) graphics.
 //!
 //! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/canvas.htm)
+//!
+//! # Scrolling
+//!
+//! A canvas can be larger than its window. Use
+//! [add_scrollbars](TkCanvas::add_scrollbars) to connect both scrollbars and
+//! keep the scroll region fitted to the items:
+//!
+//! ```ignore
+//! let canvas = rish::make_canvas(&root);
+//! let (hbar, vbar) = canvas.add_scrollbars(&root);
+//!
+//! canvas.grid().row(0).column(0).sticky(rish::Sticky::NESW).layout();
+//! vbar.grid().row(0).column(1).sticky(rish::Sticky::NS).layout();
+//! hbar.grid().row(1).column(0).sticky(rish::Sticky::EW).layout();
+//! ```
+//!
+//! Event positions are relative to the window: convert them into canvas
+//! coordinates with [canvas_position](TkCanvas::canvas_position).
+//!
+//! # Scene graph
+//!
+//! The [scene] module provides a retained-mode layer, holding shapes and
+//! groups in Rust and sending batches of changes to the canvas.
+//!
+//! # Interaction
+//!
+//! The [interaction] module provides helpers to drag items with the mouse,
+//! and to select items by drawing a rectangle around them.
+//!
+//! # Exporting
+//!
+//! The contents of a canvas can be written as PostScript using
+//! [postscript](TkCanvas::postscript), or as an image using
+//! [snapshot](TkCanvas::snapshot).
+
+use std::fmt;
+use std::str;
 
 use super::grid;
 use super::image;
 use super::pack;
+use super::place;
+use super::scrollbar;
 use super::widget;
 use super::wish;
 
+pub mod interaction;
+pub mod scene;
+
 /// Refers to a canvas widget
 #[derive(Clone, Debug, PartialEq)]
 pub struct TkCanvas {
     pub id: String,
 }
 
+/// Refers to the settings for generating PostScript from a canvas.
+///
+/// The methods on this struct set the values of different options in a
+/// builder style: pass the result to [postscript](TkCanvas::postscript).
+///
+/// Distances on the page are given in Tk's screen-distance format: a number
+/// followed by "c" (centimetres), "i" (inches), "m" (millimetres) or
+/// "p" (points), e.g. "210m".
+///
+#[derive(Clone, Debug, Default)]
+pub struct PostscriptOptions {
+    colour_mode: Option<TkColourMode>,
+    file: Option<String>,
+    height: Option<u64>,
+    page_anchor: Option<widget::Anchor>,
+    page_height: Option<String>,
+    page_width: Option<String>,
+    page_x: Option<String>,
+    page_y: Option<String>,
+    rotate: bool,
+    width: Option<u64>,
+    x: Option<i64>,
+    y: Option<i64>,
+}
+
+impl PostscriptOptions {
+    /// Sets how colour information is output.
+    pub fn colour_mode(&mut self, value: TkColourMode) -> &mut Self {
+        self.colour_mode = Some(value);
+        self
+    }
+
+    /// Writes the PostScript to given file, instead of returning it.
+    pub fn file(&mut self, filename: &str) -> &mut Self {
+        self.file = Some(String::from(filename));
+        self
+    }
+
+    /// Height of the area of the canvas to print: defaults to the
+    /// visible height of the canvas.
+    pub fn height(&mut self, value: u64) -> &mut Self {
+        self.height = Some(value);
+        self
+    }
+
+    /// Which point of the printed area is placed at the page position.
+    pub fn page_anchor(&mut self, value: widget::Anchor) -> &mut Self {
+        self.page_anchor = Some(value);
+        self
+    }
+
+    /// Height of the printed area on the page: the area is scaled to fit.
+    pub fn page_height(&mut self, distance: &str) -> &mut Self {
+        self.page_height = Some(String::from(distance));
+        self
+    }
+
+    /// Width of the printed area on the page: the area is scaled to fit.
+    pub fn page_width(&mut self, distance: &str) -> &mut Self {
+        self.page_width = Some(String::from(distance));
+        self
+    }
+
+    /// Horizontal position on the page of the page anchor.
+    pub fn page_x(&mut self, distance: &str) -> &mut Self {
+        self.page_x = Some(String::from(distance));
+        self
+    }
+
+    /// Vertical position on the page of the page anchor.
+    pub fn page_y(&mut self, distance: &str) -> &mut Self {
+        self.page_y = Some(String::from(distance));
+        self
+    }
+
+    /// Set to true to rotate the printed area into landscape orientation.
+    pub fn rotate(&mut self, value: bool) -> &mut Self {
+        self.rotate = value;
+        self
+    }
+
+    /// Width of the area of the canvas to print: defaults to the
+    /// visible width of the canvas.
+    pub fn width(&mut self, value: u64) -> &mut Self {
+        self.width = Some(value);
+        self
+    }
+
+    /// Left edge of the area of the canvas to print.
+    pub fn x(&mut self, value: i64) -> &mut Self {
+        self.x = Some(value);
+        self
+    }
+
+    /// Top edge of the area of the canvas to print.
+    pub fn y(&mut self, value: i64) -> &mut Self {
+        self.y = Some(value);
+        self
+    }
+
+    // Option-value string used for the postscript command.
+    fn options_string(&self) -> String {
+        let mut msg = String::new();
+        if let Some(mode) = &self.colour_mode {
+            let mode = match mode {
+                TkColourMode::Colour => "color",
+                TkColourMode::Grey => "gray",
+                TkColourMode::Mono => "mono",
+            };
+            msg.push_str(&format!("-colormode {} ", mode));
+        }
+        if let Some(file) = &self.file {
+            msg.push_str(&format!("-file {} ", wish::tcl_quote(file)));
+        }
+        if let Some(height) = self.height {
+            msg.push_str(&format!("-height {} ", height));
+        }
+        if let Some(anchor) = &self.page_anchor {
+            msg.push_str(&format!("-pageanchor {} ", anchor));
+        }
+        if let Some(distance) = &self.page_height {
+            msg.push_str(&format!("-pageheight {} ", distance));
+        }
+        if let Some(distance) = &self.page_width {
+            msg.push_str(&format!("-pagewidth {} ", distance));
+        }
+        if let Some(distance) = &self.page_x {
+            msg.push_str(&format!("-pagex {} ", distance));
+        }
+        if let Some(distance) = &self.page_y {
+            msg.push_str(&format!("-pagey {} ", distance));
+        }
+        msg.push_str(&format!("-rotate {} ", if self.rotate { "1" } else { "0" }));
+        if let Some(width) = self.width {
+            msg.push_str(&format!("-width {} ", width));
+        }
+        if let Some(x) = self.x {
+            msg.push_str(&format!("-x {} ", x));
+        }
+        if let Some(y) = self.y {
+            msg.push_str(&format!("-y {} ", y));
+        }
+
+        msg
+    }
+}
+
+/// Refers to a canvas item of any type, e.g. as returned by a search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TkCanvasAnyItem {
+    pub canvas: String,
+    pub id: String,
+}
+
 /// Refers to a canvas arc
 #[derive(Clone, Debug, PartialEq)]
 pub struct TkCanvasArc {
@@ -21,6 +217,13 @@ pub struct TkCanvasArc {
     pub id: String,
 }
 
+/// Refers to a canvas bitmap
+#[derive(Clone, Debug, PartialEq)]
+pub struct TkCanvasBitmap {
+    pub canvas: String,
+    pub id: String,
+}
+
 /// Refers to a canvas image
 #[derive(Clone, Debug, PartialEq)]
 pub struct TkCanvasImage {
@@ -87,8 +290,38 @@ impl widget::TkWidget for TkCanvas {
 }
 impl grid::TkGridLayout for TkCanvas {}
 impl pack::TkPackLayout for TkCanvas {}
+impl place::TkPlaceLayout for TkCanvas {}
 
 impl TkCanvas {
+    /// Creates a horizontal and a vertical scrollbar in given parent,
+    /// connected to this canvas, and keeps the scroll region fitted to
+    /// the canvas contents (see [auto_scroll_region](TkCanvas::auto_scroll_region)).
+    ///
+    /// Returns the (horizontal, vertical) scrollbars, which must still be
+    /// placed using a layout.
+    pub fn add_scrollbars(
+        &self,
+        parent: &impl widget::TkWidget,
+    ) -> (scrollbar::TkScrollbar, scrollbar::TkScrollbar) {
+        let horizontal = scrollbar::make_horizontal_scrollbar(parent, self);
+        let vertical = scrollbar::make_vertical_scrollbar(parent, self);
+        self.auto_scroll_region();
+
+        (horizontal, vertical)
+    }
+
+    /// Keeps the scroll region set to the bounding box of all items.
+    /// The region is recalculated, once the canvas is idle, after any items
+    /// are created, deleted, moved or reconfigured, and when the canvas is
+    /// resized.
+    pub fn auto_scroll_region(&self) {
+        let msg = format!(
+            "trace add execution {} leave [list rish_canvas_changed {}] ; bind {} <Configure> {{+rish_fit_scroll_region %W}} ; rish_fit_scroll_region {}",
+            &self.id, &self.id, &self.id, &self.id
+        );
+        wish::tell_wish(&msg);
+    }
+
     /// Colour of canvas background.
     ///
     /// Colours are specified as a string, by either:
@@ -99,11 +332,69 @@ impl TkCanvas {
         widget::configure(&self.id, "background", colour);
     }
 
+    /// Returns the bounding box (x1, y1, x2, y2) enclosing all items with
+    /// given tag or id ("all" for all items), or None if there are no
+    /// such items.
+    pub fn bbox(&self, tag: &str) -> Option<(i64, i64, i64, i64)> {
+        let msg = format!("puts [{} bbox {}] ; flush stdout", &self.id, tag);
+        let result = wish::ask_wish(&msg);
+        let parts: Vec<i64> = result
+            .split_whitespace()
+            .filter_map(|part| part.parse::<i64>().ok())
+            .collect();
+
+        if parts.len() == 4 {
+            Some((parts[0], parts[1], parts[2], parts[3]))
+        } else {
+            None
+        }
+    }
+
     /// Size of border around widget.
     pub fn border_width(&self, width: u64) {
         widget::configure(&self.id, "borderwidth", &width.to_string());
     }
 
+    /// Converts the position of an event, relative to the canvas window,
+    /// into a position in canvas coordinates, taking account of scrolling.
+    pub fn canvas_position(&self, event: &widget::TkEvent) -> (f64, f64) {
+        let msg = format!(
+            "puts \"[{} canvasx {}] [{} canvasy {}]\" ; flush stdout",
+            &self.id, event.x, &self.id, event.y
+        );
+        let result = wish::ask_wish(&msg);
+        let parts: Vec<f64> = result
+            .split_whitespace()
+            .filter_map(|part| part.parse::<f64>().ok())
+            .collect();
+
+        if parts.len() == 2 {
+            (parts[0], parts[1])
+        } else {
+            (event.x as f64, event.y as f64)
+        }
+    }
+
+    /// Converts an x position in the canvas window into a canvas coordinate.
+    pub fn canvas_x(&self, window_x: i64) -> f64 {
+        let msg = format!("puts [{} canvasx {}] ; flush stdout", &self.id, window_x);
+        let result = wish::ask_wish(&msg);
+        result.parse::<f64>().unwrap_or(window_x as f64)
+    }
+
+    /// Converts a y position in the canvas window into a canvas coordinate.
+    pub fn canvas_y(&self, window_y: i64) -> f64 {
+        let msg = format!("puts [{} canvasy {}] ; flush stdout", &self.id, window_y);
+        let result = wish::ask_wish(&msg);
+        result.parse::<f64>().unwrap_or(window_y as f64)
+    }
+
+    /// Set to true to prevent the view from scrolling outside the
+    /// scroll region (the default).
+    pub fn confine(&self, value: bool) {
+        widget::configure(&self.id, "confine", if value { "1" } else { "0" });
+    }
+
     /// Configures the item(s) with given tag.
     pub fn configure_tag(&self, tag: &str, option: &str, value: &str) {
         let msg = format!(
@@ -128,6 +419,25 @@ impl TkCanvas {
         }
     }
 
+    /// Creates a bitmap at (x, y) using given bitmap: either the name of one
+    /// of Tk's built-in bitmaps, such as "error" or "questhead", or the
+    /// name of an X11 bitmap file prefixed by "@".
+    pub fn create_bitmap(&self, (x, y): (u64, u64), bitmap: &str) -> TkCanvasBitmap {
+        let msg = format!(
+            "puts [{} create bitmap {} {} -bitmap {}] ; flush stdout",
+            &self.id,
+            x,
+            y,
+            wish::tcl_quote(bitmap)
+        );
+        let id = wish::ask_wish(&msg);
+
+        TkCanvasBitmap {
+            canvas: self.id.clone(),
+            id,
+        }
+    }
+
     /// Creates an image at (x, y) according to given image reference.
     pub fn create_image(&self, (x, y): (u64, u64), image: &image::TkImage) -> TkCanvasImage {
         let msg = format!(
@@ -254,6 +564,58 @@ impl TkCanvas {
         wish::tell_wish(&msg);
     }
 
+    /// Returns the items completely enclosed by the rectangle with opposite
+    /// corners (x1, y1) (x2, y2), in stacking order from lowest to highest.
+    pub fn find_enclosed(
+        &self,
+        (x1, y1): (f64, f64),
+        (x2, y2): (f64, f64),
+    ) -> Vec<TkCanvasAnyItem> {
+        self.find(&format!("enclosed {} {} {} {}", x1, y1, x2, y2))
+    }
+
+    /// Returns the items overlapping the rectangle with opposite
+    /// corners (x1, y1) (x2, y2), in stacking order from lowest to highest.
+    pub fn find_overlapping(
+        &self,
+        (x1, y1): (f64, f64),
+        (x2, y2): (f64, f64),
+    ) -> Vec<TkCanvasAnyItem> {
+        self.find(&format!("overlapping {} {} {} {}", x1, y1, x2, y2))
+    }
+
+    /// Returns the items with given tag, in stacking order from lowest to
+    /// highest.
+    pub fn find_with_tag(&self, tag: &str) -> Vec<TkCanvasAnyItem> {
+        self.find(&format!("withtag {{{}}}", tag))
+    }
+
+    fn find(&self, search: &str) -> Vec<TkCanvasAnyItem> {
+        let msg = format!("puts [{} find {}] ; flush stdout", &self.id, search);
+        let result = wish::ask_wish(&msg);
+        result
+            .split_whitespace()
+            .map(|id| TkCanvasAnyItem {
+                canvas: self.id.clone(),
+                id: String::from(id),
+            })
+            .collect()
+    }
+
+    /// Gives the keyboard focus of the canvas to given text item, so it
+    /// receives key events and shows its insertion cursor (the canvas itself
+    /// must also have the focus).
+    pub fn focus_item(&self, item: &TkCanvasText) {
+        let msg = format!("{} focus {}", &self.id, &item.id);
+        wish::tell_wish(&msg);
+    }
+
+    /// Sets the scroll region to the current bounding box of all items.
+    pub fn fit_scroll_region(&self) {
+        let msg = format!("rish_fit_scroll_region {}", &self.id);
+        wish::tell_wish(&msg);
+    }
+
     /// Height of canvas, in pixels.
     pub fn height(&self, height: u64) {
         widget::configure(&self.id, "height", &height.to_string());
@@ -264,6 +626,56 @@ impl TkCanvas {
         widget::configure(&self.id, "relief", &value.to_string());
     }
 
+    /// Generates a PostScript representation of the canvas contents.
+    ///
+    /// Returns the PostScript, or an empty string if a file was given
+    /// in the options, or an error if Tk could not generate it.
+    pub fn postscript(&self, options: &PostscriptOptions) -> Result<String, wish::TkError> {
+        let msg = format!(
+            "update idletasks ; {} postscript {}",
+            &self.id,
+            options.options_string()
+        );
+        wish::eval_wish(&msg)
+    }
+
+    /// Clears the selection of text, if it is in this canvas.
+    pub fn select_clear(&self) {
+        let msg = format!("{} select clear", &self.id);
+        wish::tell_wish(&msg);
+    }
+
+    /// Sets the area of the canvas which can be scrolled to, with
+    /// opposite corners (x1, y1) (x2, y2).
+    pub fn scroll_region(&self, (x1, y1): (i64, i64), (x2, y2): (i64, i64)) {
+        let msg = format!(
+            "{} configure -scrollregion {{{} {} {} {}}}",
+            &self.id, x1, y1, x2, y2
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Saves an image of the canvas, as currently displayed, to given file
+    /// in given format.
+    ///
+    /// This requires the [Img](https://sourceforge.net/projects/tkimg/)
+    /// package (tkimg), to capture the canvas window: an error is returned
+    /// if it is not available. The canvas must be mapped on a display,
+    /// though that can be a virtual one, such as Xvfb.
+    pub fn snapshot(
+        &self,
+        filename: &str,
+        format: image::ImageFormat,
+    ) -> Result<(), wish::TkError> {
+        let msg = format!(
+            "package require Img ; update ; set ::rish_snapshot [image create photo -format window -data {}] ; try {{ $::rish_snapshot write {} -format {} }} finally {{ image delete $::rish_snapshot }}",
+            &self.id,
+            wish::tcl_quote(filename),
+            format
+        );
+        wish::eval_wish(&msg).map(|_| ())
+    }
+
     /// Sets the state of the widget.
     pub fn state(&self, value: widget::State) {
         widget::configure(&self.id, "state", &value.to_string());
@@ -273,6 +685,58 @@ impl TkCanvas {
     pub fn width(&self, width: u64) {
         widget::configure(&self.id, "width", &width.to_string());
     }
+
+    /// Distance, in pixels, of one horizontal scrolling unit.
+    pub fn x_scroll_increment(&self, value: u64) {
+        widget::configure(&self.id, "xscrollincrement", &value.to_string());
+    }
+
+    /// Returns the visible horizontal part of the scroll region, as a
+    /// pair of fractions (first, last) in the range 0 to 1.
+    pub fn x_view(&self) -> (f64, f64) {
+        let msg = format!("puts [{} xview] ; flush stdout", &self.id);
+        let result = wish::ask_wish(&msg);
+        widget::view_fractions(&result)
+    }
+
+    /// Scrolls view so given fraction of the scroll region is off-screen
+    /// to the left.
+    pub fn x_view_moveto(&self, fraction: f64) {
+        let msg = format!("{} xview moveto {}", &self.id, fraction);
+        wish::tell_wish(&msg);
+    }
+
+    /// Scrolls view left (negative) or right (positive) by given amount.
+    pub fn x_view_scroll(&self, amount: i64, units: widget::ScrollUnits) {
+        let msg = format!("{} xview scroll {} {}", &self.id, amount, units);
+        wish::tell_wish(&msg);
+    }
+
+    /// Distance, in pixels, of one vertical scrolling unit.
+    pub fn y_scroll_increment(&self, value: u64) {
+        widget::configure(&self.id, "yscrollincrement", &value.to_string());
+    }
+
+    /// Returns the visible vertical part of the scroll region, as a
+    /// pair of fractions (first, last) in the range 0 to 1.
+    pub fn y_view(&self) -> (f64, f64) {
+        let msg = format!("puts [{} yview] ; flush stdout", &self.id);
+        let result = wish::ask_wish(&msg);
+        widget::view_fractions(&result)
+    }
+
+    /// Scrolls view so given fraction of the scroll region is off-screen
+    /// to the top.
+    pub fn y_view_moveto(&self, fraction: f64) {
+        let msg = format!("{} yview moveto {}", &self.id, fraction);
+        wish::tell_wish(&msg);
+    }
+
+    /// Scrolls view up (negative) or down (positive) by given amount.
+    pub fn y_view_scroll(&self, amount: i64, units: widget::ScrollUnits) {
+        let msg = format!("{} yview scroll {} {}", &self.id, amount, units);
+        wish::tell_wish(&msg);
+    }
 }
 
 // -- functionality for each of the canvas items
@@ -285,7 +749,7 @@ pub trait TkCanvasItem {
     /// Binds event to item.
     fn bind(&self, pattern: &str, command: impl Fn(widget::TkEvent) + Send + 'static) {
         // tag+pattern used as identifier, as multiple commands can be bound to each entity
-        let tag_pattern = format!("{}{}{}", self.canvas(), self.id(), pattern);
+        let tag_pattern = wish::callback_key(self.canvas(), &format!("{}{}", self.id(), pattern));
         wish::add_callback1_event(&tag_pattern, wish::mk_callback1_event(command));
         let msg = format!(
             "{} bind {} {} {{ puts cb1e:{}:%x:%y:%X:%Y:%h:%w:%k:%K:%b ; flush stdout }}",
@@ -297,6 +761,24 @@ pub trait TkCanvasItem {
         wish::tell_wish(&msg);
     }
 
+    /// Retrieves the value of a configuration option of the item
+    /// as a string, or the error reported by Tk, e.g. for an unknown
+    /// option or a deleted item.
+    fn cget(&self, option: &str) -> Result<String, wish::TkError> {
+        let msg = format!("{} itemcget {} -{}", self.canvas(), self.id(), option);
+        wish::eval_wish(&msg)
+    }
+
+    /// Retrieves the value of a configuration option of the item,
+    /// converted to the requested type, e.g. `item.cget_as::<f64>("width")`.
+    /// Returns an error if Tk reports one, or the value cannot be converted.
+    fn cget_as<T: str::FromStr>(&self, option: &str) -> Result<T, wish::TkError> {
+        let value = self.cget(option)?;
+        value.parse::<T>().map_err(|_| {
+            wish::TkError::new(&format!("Invalid value for option -{}: {}", option, value))
+        })
+    }
+
     /// Configures the individual item.
     fn configure(&self, option: &str, value: &str) {
         let msg = format!(
@@ -308,6 +790,26 @@ pub trait TkCanvasItem {
         );
         wish::tell_wish(&msg);
     }
+
+    /// Returns the coordinates of the item, as a list of x, y values.
+    fn coords(&self) -> Vec<f64> {
+        let msg = format!(
+            "puts [{} coords {}] ; flush stdout",
+            self.canvas(),
+            self.id()
+        );
+        let result = wish::ask_wish(&msg);
+        result
+            .split_whitespace()
+            .filter_map(|value| value.parse::<f64>().ok())
+            .collect()
+    }
+
+    /// Returns the type of the item, e.g. "line" or "rectangle".
+    fn item_type(&self) -> String {
+        let msg = format!("puts [{} type {}] ; flush stdout", self.canvas(), self.id());
+        wish::ask_wish(&msg)
+    }
 }
 
 /// Each item can have one or more named tags attached to it.
@@ -354,6 +856,14 @@ pub enum TkArcStyle {
     PieSlice,
 }
 
+/// Specifies how colour is output in PostScript.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TkColourMode {
+    Colour,
+    Grey,
+    Mono,
+}
+
 /// Specifies how ends of lines are drawn.
 pub enum TkCapStyle {
     Butt,
@@ -376,6 +886,106 @@ pub enum TkJoinStyle {
     Round,
 }
 
+/// Specifies how lines, and polygon outlines, are smoothed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TkSmoothing {
+    /// Points are joined with straight segments.
+    None,
+    /// A parabolic spline is drawn through the points.
+    Spline,
+    /// The points define a Bezier curve: a straight segment uses three
+    /// identical points, and a curved segment uses two control points.
+    Raw,
+}
+
+impl fmt::Display for TkSmoothing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            TkSmoothing::None => "0",
+            TkSmoothing::Spline => "true",
+            TkSmoothing::Raw => "raw",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
+/// Specifies a character position within a canvas text item.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TkCanvasTextIndex {
+    /// The character at given (0-indexed) position.
+    Char(u64),
+    /// The position just after the last character.
+    End,
+    /// The character just after the insertion cursor.
+    Insert,
+    /// The first selected character.
+    SelFirst,
+    /// The last selected character.
+    SelLast,
+    /// The character closest to given canvas (x, y) coordinates.
+    At(i64, i64),
+}
+
+impl fmt::Display for TkCanvasTextIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TkCanvasTextIndex::Char(index) => write!(f, "{}", index),
+            TkCanvasTextIndex::End => write!(f, "end"),
+            TkCanvasTextIndex::Insert => write!(f, "insert"),
+            TkCanvasTextIndex::SelFirst => write!(f, "sel.first"),
+            TkCanvasTextIndex::SelLast => write!(f, "sel.last"),
+            TkCanvasTextIndex::At(x, y) => write!(f, "@{},{}", x, y),
+        }
+    }
+}
+
+impl TkCanvasItem for TkCanvasBitmap {
+    fn canvas(&self) -> &str {
+        &self.canvas
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl TkCanvasTags for TkCanvasBitmap {}
+
+impl TkCanvasBitmap {
+    /// Positioning of bitmap with respect to its (x, y) position.
+    pub fn anchor(&self, value: widget::Anchor) {
+        self.configure("anchor", &value.to_string());
+    }
+
+    /// Colour for the background (0) pixels of the bitmap: an empty string
+    /// makes them transparent.
+    pub fn background(&self, colour: &str) {
+        self.configure("background", colour);
+    }
+
+    /// Changes the bitmap to display.
+    pub fn bitmap(&self, bitmap: &str) {
+        self.configure("bitmap", bitmap);
+    }
+
+    /// Colour for the foreground (1) pixels of the bitmap.
+    pub fn foreground(&self, colour: &str) {
+        self.configure("foreground", colour);
+    }
+}
+
+impl TkCanvasItem for TkCanvasAnyItem {
+    fn canvas(&self) -> &str {
+        &self.canvas
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl TkCanvasTags for TkCanvasAnyItem {}
+
 impl TkCanvasItem for TkCanvasArc {
     fn canvas(&self) -> &str {
         &self.canvas
@@ -528,6 +1138,16 @@ impl TkCanvasLine {
         };
         self.configure("joinstyle", value);
     }
+
+    /// How the line is smoothed.
+    pub fn smooth(&self, value: TkSmoothing) {
+        self.configure("smooth", &value.to_string());
+    }
+
+    /// Number of line segments used to draw each curve of a smoothed line.
+    pub fn spline_steps(&self, value: u64) {
+        self.configure("splinesteps", &value.to_string());
+    }
 }
 
 impl TkCanvasItem for TkCanvasOval {
@@ -618,6 +1238,17 @@ impl TkCanvasPolygon {
         };
         self.configure("joinstyle", value);
     }
+
+    /// How the outline of the polygon is smoothed.
+    pub fn smooth(&self, value: TkSmoothing) {
+        self.configure("smooth", &value.to_string());
+    }
+
+    /// Number of line segments used to draw each curve of a smoothed
+    /// outline.
+    pub fn spline_steps(&self, value: u64) {
+        self.configure("splinesteps", &value.to_string());
+    }
 }
 
 impl TkCanvasItem for TkCanvasRectangle {
@@ -691,21 +1322,79 @@ impl TkCanvasText {
         self.configure("angle", &degrees.to_string());
     }
 
+    /// Deletes the characters from `first` up to and including `last`.
+    pub fn delete_chars(&self, first: TkCanvasTextIndex, last: TkCanvasTextIndex) {
+        let msg = format!("{} dchars {} {} {}", &self.canvas, &self.id, first, last);
+        wish::tell_wish(&msg);
+    }
+
     /// Specifies the font to use for text.
     pub fn font(&self, definition: &str) {
         self.configure("font", definition);
     }
 
+    /// Returns the numerical position of given index.
+    pub fn index(&self, index: TkCanvasTextIndex) -> u64 {
+        let msg = format!(
+            "puts [{} index {} {}] ; flush stdout",
+            &self.canvas, &self.id, index
+        );
+        let result = wish::ask_wish(&msg);
+        result.parse::<u64>().unwrap_or(0)
+    }
+
+    /// Places the insertion cursor just before the character at given index.
+    pub fn insert_cursor(&self, index: TkCanvasTextIndex) {
+        let msg = format!("{} icursor {} {}", &self.canvas, &self.id, index);
+        wish::tell_wish(&msg);
+    }
+
+    /// Inserts text just before the character at given index.
+    pub fn insert(&self, index: TkCanvasTextIndex, text: &str) {
+        let msg = format!(
+            "{} insert {} {} {}",
+            &self.canvas,
+            &self.id,
+            index,
+            wish::tcl_quote(text)
+        );
+        wish::tell_wish(&msg);
+    }
+
     /// Alignment of text within its bounding region.
     pub fn justify(&self, value: widget::Justify) {
         widget::configure(&self.id, "justify", &value.to_string());
     }
 
+    /// Adjusts the end of the selection nearest to given index, so the
+    /// selection extends to that index.
+    pub fn select_adjust(&self, index: TkCanvasTextIndex) {
+        let msg = format!("{} select adjust {} {}", &self.canvas, &self.id, index);
+        wish::tell_wish(&msg);
+    }
+
+    /// Sets the anchor point for the selection at given index.
+    pub fn select_from(&self, index: TkCanvasTextIndex) {
+        let msg = format!("{} select from {} {}", &self.canvas, &self.id, index);
+        wish::tell_wish(&msg);
+    }
+
+    /// Selects the characters between the anchor point and given index.
+    pub fn select_to(&self, index: TkCanvasTextIndex) {
+        let msg = format!("{} select to {} {}", &self.canvas, &self.id, index);
+        wish::tell_wish(&msg);
+    }
+
     /// Sets the text to display.
     pub fn text(&self, value: &str) {
         self.configure("text", value);
     }
 
+    /// Returns the text displayed.
+    pub fn text_get(&self) -> Result<String, wish::TkError> {
+        self.cget("text")
+    }
+
     /// Underlines the character at the given index position.
     pub fn underline(&self, index: u64) {
         self.configure("underline", &index.to_string());
@@ -734,4 +1423,21 @@ impl TkCanvasWidget {
     pub fn anchor(&self, value: widget::Anchor) {
         self.configure("anchor", &value.to_string());
     }
+
+    /// Height of the widget, in pixels: by default, the widget's own
+    /// requested height is used.
+    pub fn height(&self, value: u64) {
+        self.configure("height", &value.to_string());
+    }
+
+    /// Width of the widget, in pixels: by default, the widget's own
+    /// requested width is used.
+    pub fn width(&self, value: u64) {
+        self.configure("width", &value.to_string());
+    }
+
+    /// Changes the widget displayed by this item.
+    pub fn window(&self, widget: &impl widget::TkWidget) {
+        self.configure("window", widget.id());
+    }
 }
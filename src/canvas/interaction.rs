@@ -0,0 +1,402 @@
+//! Interaction helpers - dragging canvas items and rubber-band selection.
+//!
+//! These helpers bind the mouse press, motion and release events needed to
+//! track a drag, so applications only supply what to do as it happens.
+//! Positions are reported in canvas coordinates, so the helpers work on
+//! scrolled canvases.
+//!
+//! # Dragging
+//!
+//! ```ignore
+//! use rish::interaction::{make_draggable, DragAxis, DragConstraints};
+//!
+//! let rect = canvas.create_rectangle((10, 10), (50, 30));
+//! let drag = make_draggable(&rect, DragConstraints::default().axis(DragAxis::Horizontal));
+//! drag.on_drag_end(|event| println!("moved by {}", event.dx));
+//! ```
+//!
+//! # Rubber-band selection
+//!
+//! Pressing the left mouse button over an empty part of the canvas and
+//! dragging draws a selection rectangle. On release, the items within the
+//! rectangle are given the [SELECTION_TAG] tag, and passed to the callback:
+//!
+//! ```ignore
+//! rish::interaction::rubber_band_select(&canvas, |items| {
+//!     println!("{} items selected", items.len());
+//! });
+//! ```
+//!
+
+use std::sync::{Arc, Mutex};
+
+use crate::canvas;
+use crate::canvas::{TkCanvasItem, TkCanvasTags};
+use crate::widget;
+use crate::wish;
+
+/// Tag attached to the items selected by [rubber_band_select].
+pub const SELECTION_TAG: &str = "rish_selected";
+
+// Tag of the rectangle drawn during a rubber-band selection.
+const RUBBER_BAND_TAG: &str = "rish_rubber_band";
+
+/// Restricts the directions in which an item can be dragged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum DragAxis {
+    #[default]
+    Both,
+    Horizontal,
+    Vertical,
+}
+
+/// Refers to the constraints on how an item is dragged.
+///
+/// The methods on this struct set the values of different options in a
+/// builder style: pass the result to [make_draggable].
+///
+#[derive(Clone, Debug, Default)]
+pub struct DragConstraints {
+    axis: DragAxis,
+    grid: Option<f64>,
+}
+
+impl DragConstraints {
+    /// Restricts dragging to the given direction(s).
+    pub fn axis(&mut self, value: DragAxis) -> &mut Self {
+        self.axis = value;
+        self
+    }
+
+    /// Moves the item in steps of the given size, in pixels.
+    pub fn snap_to_grid(&mut self, size: f64) -> &mut Self {
+        self.grid = if size > 0.0 { Some(size) } else { None };
+        self
+    }
+
+    // Converts the distance moved by the mouse into the distance the item
+    // is allowed to move.
+    fn constrain(&self, dx: f64, dy: f64) -> (f64, f64) {
+        let (dx, dy) = match self.axis {
+            DragAxis::Both => (dx, dy),
+            DragAxis::Horizontal => (dx, 0.0),
+            DragAxis::Vertical => (0.0, dy),
+        };
+        if let Some(size) = self.grid {
+            ((dx / size).round() * size, (dy / size).round() * size)
+        } else {
+            (dx, dy)
+        }
+    }
+}
+
+/// Information about a drag, passed to the drag callbacks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DragEvent {
+    /// x-coordinate of the mouse, in canvas coordinates
+    pub x: f64,
+    /// y-coordinate of the mouse, in canvas coordinates
+    pub y: f64,
+    /// horizontal distance the item has moved since the drag started
+    pub dx: f64,
+    /// vertical distance the item has moved since the drag started
+    pub dy: f64,
+}
+
+type DragCallback = Box<dyn Fn(DragEvent) + Send + 'static>;
+
+struct DragState {
+    constraints: DragConstraints,
+    start: Option<(f64, f64)>,
+    moved: (f64, f64),
+    on_start: Option<DragCallback>,
+    on_move: Option<DragCallback>,
+    on_end: Option<DragCallback>,
+}
+
+/// Refers to a draggable canvas item, used to set the drag callbacks.
+#[derive(Clone)]
+pub struct Draggable {
+    state: Arc<Mutex<DragState>>,
+}
+
+impl Draggable {
+    /// Sets command to call when the item is pressed, starting a drag.
+    pub fn on_drag_start(&self, command: impl Fn(DragEvent) + Send + 'static) {
+        self.state.lock().unwrap().on_start = Some(Box::new(command));
+    }
+
+    /// Sets command to call each time the item is moved during a drag.
+    pub fn on_drag_move(&self, command: impl Fn(DragEvent) + Send + 'static) {
+        self.state.lock().unwrap().on_move = Some(Box::new(command));
+    }
+
+    /// Sets command to call when the item is released, ending a drag.
+    pub fn on_drag_end(&self, command: impl Fn(DragEvent) + Send + 'static) {
+        self.state.lock().unwrap().on_end = Some(Box::new(command));
+    }
+}
+
+/// Makes a canvas item draggable with the left mouse button, subject to
+/// the given constraints.
+///
+/// Calling this again for the same item replaces its constraints, and
+/// the returned value is then used to set its callbacks.
+pub fn make_draggable(item: &impl TkCanvasItem, constraints: &DragConstraints) -> Draggable {
+    let state = Arc::new(Mutex::new(DragState {
+        constraints: constraints.clone(),
+        start: None,
+        moved: (0.0, 0.0),
+        on_start: None,
+        on_move: None,
+        on_end: None,
+    }));
+
+    let press_state = state.clone();
+    bind_canvas_event(
+        item.canvas(),
+        Some(item.id()),
+        "<ButtonPress-1>",
+        "",
+        move |event| {
+            let (x, y) = (event.x as f64, event.y as f64);
+            {
+                let mut state = press_state.lock().unwrap();
+                state.start = Some((x, y));
+                state.moved = (0.0, 0.0);
+            }
+            call_unlocked(
+                &press_state,
+                |state| &mut state.on_start,
+                DragEvent {
+                    x,
+                    y,
+                    dx: 0.0,
+                    dy: 0.0,
+                },
+            );
+        },
+    );
+
+    let canvas = String::from(item.canvas());
+    let id = String::from(item.id());
+    let motion_state = state.clone();
+    bind_canvas_event(
+        item.canvas(),
+        Some(item.id()),
+        "<B1-Motion>",
+        "",
+        move |event| {
+            let (x, y) = (event.x as f64, event.y as f64);
+            let moved = {
+                let mut state = motion_state.lock().unwrap();
+                match state.start {
+                    Some((start_x, start_y)) => {
+                        let (dx, dy) = state.constraints.constrain(x - start_x, y - start_y);
+                        let (step_x, step_y) = (dx - state.moved.0, dy - state.moved.1);
+                        if step_x != 0.0 || step_y != 0.0 {
+                            let msg = format!("{} move {} {} {}", &canvas, &id, step_x, step_y);
+                            wish::tell_wish(&msg);
+                            state.moved = (dx, dy);
+                            Some((dx, dy))
+                        } else {
+                            None
+                        }
+                    }
+                    None => None,
+                }
+            };
+            if let Some((dx, dy)) = moved {
+                call_unlocked(
+                    &motion_state,
+                    |state| &mut state.on_move,
+                    DragEvent { x, y, dx, dy },
+                );
+            }
+        },
+    );
+
+    let release_state = state.clone();
+    bind_canvas_event(
+        item.canvas(),
+        Some(item.id()),
+        "<ButtonRelease-1>",
+        "",
+        move |event| {
+            let moved = {
+                let mut state = release_state.lock().unwrap();
+                state.start.take().map(|_| state.moved)
+            };
+            if let Some((dx, dy)) = moved {
+                call_unlocked(
+                    &release_state,
+                    |state| &mut state.on_end,
+                    DragEvent {
+                        x: event.x as f64,
+                        y: event.y as f64,
+                        dx,
+                        dy,
+                    },
+                );
+            }
+        },
+    );
+
+    Draggable { state }
+}
+
+// Calls one of the drag callbacks with the drag state unlocked, so the
+// callback can itself use the draggable item. The callback is restored
+// afterwards, unless a replacement was set while it ran.
+fn call_unlocked(
+    state: &Mutex<DragState>,
+    callback: fn(&mut DragState) -> &mut Option<DragCallback>,
+    event: DragEvent,
+) {
+    let command = callback(&mut state.lock().unwrap()).take();
+    if let Some(command) = command {
+        command(event);
+        let mut state = state.lock().unwrap();
+        let slot = callback(&mut state);
+        if slot.is_none() {
+            *slot = Some(command);
+        }
+    }
+}
+
+/// Lets the user select items by dragging out a rectangle, starting over
+/// an empty part of the canvas with the left mouse button.
+///
+/// When the button is released, the previous selection is cleared, the
+/// items completely enclosed by the rectangle are given the [SELECTION_TAG]
+/// tag, and the command is called with the selected items.
+pub fn rubber_band_select(
+    canvas: &canvas::TkCanvas,
+    command: impl Fn(Vec<canvas::TkCanvasAnyItem>) + Send + 'static,
+) {
+    let start: Arc<Mutex<Option<(i64, i64)>>> = Arc::new(Mutex::new(None));
+
+    // -- only start when the press is not over an item
+    let press_start = start.clone();
+    let press_canvas = canvas.id.clone();
+    bind_canvas_event(
+        &canvas.id,
+        None,
+        "<ButtonPress-1>",
+        "[%W find withtag current] eq {}",
+        move |event| {
+            *press_start.lock().unwrap() = Some((event.x, event.y));
+            let msg = format!(
+                "{} delete {} ; {} create rectangle {} {} {} {} -dash {{-}} -tags {}",
+                &press_canvas,
+                RUBBER_BAND_TAG,
+                &press_canvas,
+                event.x,
+                event.y,
+                event.x,
+                event.y,
+                RUBBER_BAND_TAG
+            );
+            wish::tell_wish(&msg);
+        },
+    );
+
+    let motion_start = start.clone();
+    let motion_canvas = canvas.id.clone();
+    bind_canvas_event(&canvas.id, None, "<B1-Motion>", "", move |event| {
+        if let Some((x, y)) = *motion_start.lock().unwrap() {
+            let msg = format!(
+                "{} coords {} {} {} {} {}",
+                &motion_canvas, RUBBER_BAND_TAG, x, y, event.x, event.y
+            );
+            wish::tell_wish(&msg);
+        }
+    });
+
+    let release_canvas = canvas.clone();
+    bind_canvas_event(&canvas.id, None, "<ButtonRelease-1>", "", move |event| {
+        let started = start.lock().unwrap().take();
+        if let Some((x, y)) = started {
+            let msg = format!(
+                "{} delete {} ; {} dtag all {}",
+                &release_canvas.id, RUBBER_BAND_TAG, &release_canvas.id, SELECTION_TAG
+            );
+            wish::tell_wish(&msg);
+
+            let items = release_canvas.find_enclosed(
+                (x.min(event.x) as f64, y.min(event.y) as f64),
+                (x.max(event.x) as f64, y.max(event.y) as f64),
+            );
+            for item in &items {
+                item.add_tag(SELECTION_TAG);
+            }
+            command(items);
+        }
+    });
+}
+
+/// Returns the items currently selected using [rubber_band_select].
+pub fn selected_items(canvas: &canvas::TkCanvas) -> Vec<canvas::TkCanvasAnyItem> {
+    canvas.find_with_tag(SELECTION_TAG)
+}
+
+// Binds command to an event on a canvas item, or on the canvas itself,
+// adding to any existing bindings. The event position is given in canvas
+// coordinates. If `condition` is not empty, the command is only called
+// when that tcl expression is true. Binding the same event again replaces
+// the command, rather than adding a second binding.
+fn bind_canvas_event(
+    canvas: &str,
+    item: Option<&str>,
+    pattern: &str,
+    condition: &str,
+    command: impl Fn(widget::TkEvent) + Send + 'static,
+) {
+    let key = wish::callback_key(
+        canvas,
+        &format!("interaction{}{}", item.unwrap_or(""), pattern),
+    );
+    wish::add_callback1_event(&key, wish::mk_callback1_event(command));
+
+    let report = format!(
+        "puts cb1e:{}:[expr {{round([%W canvasx %x])}}]:[expr {{round([%W canvasy %y])}}]:%X:%Y:%h:%w:%k:%K:%b ; flush stdout",
+        key
+    );
+    let script = if condition.is_empty() {
+        report
+    } else {
+        format!("if {{{}}} {{ {} }}", condition, report)
+    };
+    let bind = match item {
+        Some(item) => format!("{} bind {} {}", canvas, item, pattern),
+        None => format!("bind {} {}", canvas, pattern),
+    };
+    // -- the callback key is reported by the script, so already bound if
+    // the existing binding mentions it
+    let msg = format!(
+        "if {{[string first {} [{}]] < 0}} {{ {} {{+{}}} }}",
+        wish::tcl_quote(&format!("cb1e:{}:", key)),
+        bind,
+        bind,
+        script
+    );
+    wish::tell_wish(&msg);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constrain_drag() {
+        let mut constraints = DragConstraints::default();
+        assert_eq!((3.0, -4.0), constraints.constrain(3.0, -4.0));
+
+        constraints.axis(DragAxis::Vertical);
+        assert_eq!((0.0, -4.0), constraints.constrain(3.0, -4.0));
+
+        constraints.axis(DragAxis::Both).snap_to_grid(10.0);
+        assert_eq!((10.0, 0.0), constraints.constrain(6.0, -4.0));
+        assert_eq!((20.0, 30.0), constraints.constrain(24.0, 26.0));
+    }
+}
@@ -0,0 +1,833 @@
+//! Scene graph - a retained-mode drawing layer over a canvas.
+//!
+//! Shapes, and groups of shapes, are held in a [Scene] on the Rust side,
+//! where they can be changed freely. Calling [commit](Scene::commit) brings
+//! the canvas up to date, sending all the changes made since the last commit
+//! to wish as a single message.
+//!
+//! * every node, shape or group, is referred to by a [NodeId]
+//! * every group maps to a canvas tag, which is attached to all the shapes
+//!   within it
+//! * every group has a [Transform], which is applied to its shapes using the
+//!   canvas `scale` and `move` commands on the group's tag
+//! * shapes are stacked in tree order: a group's children are drawn in the
+//!   order they were added, each above the ones before, unless moved with
+//!   [raise](Scene::raise) or [lower](Scene::lower)
+//! * [hit_test](Scene::hit_test) returns the shapes at a point as NodeIds
+//!
+//! # Example
+//!
+//! ```ignore
+//! use rish::scene::{Scene, Shape, Transform};
+//!
+//! let canvas = rish::make_canvas(&root);
+//! let mut scene = Scene::new(&canvas);
+//!
+//! let group = scene.add_group(scene.root());
+//! let square = scene.add_shape(group, Shape::Rectangle((0.0, 0.0), (20.0, 20.0)));
+//! scene.configure(square, "fill", "red");
+//! scene.commit(); // creates the square
+//!
+//! scene.set_transform(group, Transform::translate(50.0, 10.0));
+//! scene.commit(); // moves the group's tag
+//! ```
+//!
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::canvas;
+use crate::wish;
+
+/// Refers to a node - a shape or a group - within a scene.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NodeId(u64);
+
+/// Geometry of a shape, in the coordinates of its parent group.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// A line through the given points.
+    Line(Vec<(f64, f64)>),
+    /// An oval filling the rectangle with given opposite corners.
+    Oval((f64, f64), (f64, f64)),
+    /// A polygon with the given vertices.
+    Polygon(Vec<(f64, f64)>),
+    /// A rectangle with given opposite corners.
+    Rectangle((f64, f64), (f64, f64)),
+    /// Some text, positioned at the given point.
+    Text((f64, f64), String),
+}
+
+impl Shape {
+    // Name of the canvas item type.
+    fn kind(&self) -> &str {
+        match self {
+            Shape::Line(_) => "line",
+            Shape::Oval(_, _) => "oval",
+            Shape::Polygon(_) => "polygon",
+            Shape::Rectangle(_, _) => "rectangle",
+            Shape::Text(_, _) => "text",
+        }
+    }
+
+    fn points(&self) -> Vec<(f64, f64)> {
+        match self {
+            Shape::Line(points) | Shape::Polygon(points) => points.clone(),
+            Shape::Oval(p1, p2) | Shape::Rectangle(p1, p2) => vec![*p1, *p2],
+            Shape::Text(point, _) => vec![*point],
+        }
+    }
+}
+
+/// A scaling followed by a translation: maps a point (x, y) to
+/// (x * scale_x + dx, y * scale_y + dy).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub scale_x: f64,
+    pub scale_y: f64,
+    pub dx: f64,
+    pub dy: f64,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+impl Transform {
+    /// The transform which leaves all points unchanged.
+    pub fn identity() -> Transform {
+        Transform {
+            scale_x: 1.0,
+            scale_y: 1.0,
+            dx: 0.0,
+            dy: 0.0,
+        }
+    }
+
+    /// A transform which scales about the origin.
+    pub fn scale(scale_x: f64, scale_y: f64) -> Transform {
+        Transform {
+            scale_x,
+            scale_y,
+            ..Transform::identity()
+        }
+    }
+
+    /// A transform which moves points by the given offset.
+    pub fn translate(dx: f64, dy: f64) -> Transform {
+        Transform {
+            dx,
+            dy,
+            ..Transform::identity()
+        }
+    }
+
+    /// Returns the transform applying this transform and then `other`.
+    pub fn then(&self, other: &Transform) -> Transform {
+        Transform {
+            scale_x: self.scale_x * other.scale_x,
+            scale_y: self.scale_y * other.scale_y,
+            dx: self.dx * other.scale_x + other.dx,
+            dy: self.dy * other.scale_y + other.dy,
+        }
+    }
+
+    /// Applies the transform to a point.
+    pub fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (x * self.scale_x + self.dx, y * self.scale_y + self.dy)
+    }
+
+    // Returns the reverse transform, or None if it scales by zero.
+    fn inverse(&self) -> Option<Transform> {
+        if self.scale_x.abs() < EPSILON || self.scale_y.abs() < EPSILON {
+            None
+        } else {
+            Some(Transform {
+                scale_x: 1.0 / self.scale_x,
+                scale_y: 1.0 / self.scale_y,
+                dx: -self.dx / self.scale_x,
+                dy: -self.dy / self.scale_y,
+            })
+        }
+    }
+
+    fn is_scaling(&self) -> bool {
+        !close(self.scale_x, 1.0) || !close(self.scale_y, 1.0)
+    }
+
+    fn is_translating(&self) -> bool {
+        !close(self.dx, 0.0) || !close(self.dy, 0.0)
+    }
+}
+
+const EPSILON: f64 = 1e-9;
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= EPSILON * (1.0 + a.abs().max(b.abs()))
+}
+
+fn close_points(a: &[(f64, f64)], b: &[(f64, f64)]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(p, q)| close(p.0, q.0) && close(p.1, q.1))
+}
+
+// State of a shape, as last sent to the canvas.
+#[derive(Clone, Debug)]
+struct Committed {
+    kind: String,
+    points: Vec<(f64, f64)>,
+    options: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug)]
+enum NodeKind {
+    Group {
+        transform: Transform,
+        // world transform currently reflected by the group's canvas items
+        canvas_world: Option<Transform>,
+    },
+    Shape {
+        shape: Shape,
+        options: BTreeMap<String, String>,
+        committed: Option<Committed>,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    kind: NodeKind,
+}
+
+/// A retained-mode collection of shapes and groups drawn on a canvas.
+#[derive(Clone, Debug)]
+pub struct Scene {
+    canvas: String,
+    prefix: String,
+    nodes: HashMap<NodeId, Node>,
+    next_id: u64,
+    // commands to run before/after the diff on the next commit
+    deletions: Vec<String>,
+    reorders: Vec<String>,
+}
+
+impl Scene {
+    /// Creates an empty scene, drawing on given canvas.
+    pub fn new(canvas: &canvas::TkCanvas) -> Scene {
+        wish::next_wid(".");
+        let prefix = format!("rish_scene{}", wish::current_id());
+        Scene::with_prefix(&canvas.id, &prefix)
+    }
+
+    fn with_prefix(canvas: &str, prefix: &str) -> Scene {
+        let mut nodes = HashMap::new();
+        nodes.insert(
+            NodeId(0),
+            Node {
+                parent: None,
+                children: vec![],
+                kind: NodeKind::Group {
+                    transform: Transform::identity(),
+                    canvas_world: None,
+                },
+            },
+        );
+
+        Scene {
+            canvas: String::from(canvas),
+            prefix: String::from(prefix),
+            nodes,
+            next_id: 1,
+            deletions: vec![],
+            reorders: vec![],
+        }
+    }
+
+    /// The root group, which contains all other nodes. Its transform
+    /// applies to the whole scene, e.g. to pan or zoom.
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    /// Adds a new, empty group to given parent group.
+    pub fn add_group(&mut self, parent: NodeId) -> NodeId {
+        self.add_node(
+            parent,
+            NodeKind::Group {
+                transform: Transform::identity(),
+                canvas_world: None,
+            },
+        )
+    }
+
+    /// Adds a new shape to given parent group.
+    pub fn add_shape(&mut self, parent: NodeId, shape: Shape) -> NodeId {
+        self.add_node(
+            parent,
+            NodeKind::Shape {
+                shape,
+                options: BTreeMap::new(),
+                committed: None,
+            },
+        )
+    }
+
+    fn add_node(&mut self, parent: NodeId, kind: NodeKind) -> NodeId {
+        let parent = if self.is_group(parent) {
+            parent
+        } else {
+            self.root()
+        };
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        self.nodes.insert(
+            id,
+            Node {
+                parent: Some(parent),
+                children: vec![],
+                kind,
+            },
+        );
+        if let Some(node) = self.nodes.get_mut(&parent) {
+            node.children.push(id);
+        }
+
+        id
+    }
+
+    /// Returns the child nodes of a group, in the order they were added.
+    pub fn children(&self, node: NodeId) -> Vec<NodeId> {
+        self.nodes
+            .get(&node)
+            .map(|node| node.children.clone())
+            .unwrap_or_default()
+    }
+
+    /// Sets a canvas item option, such as "fill" or "width", for a shape.
+    /// For the available options, see the Tk
+    /// [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/canvas.htm#M26).
+    pub fn configure(&mut self, node: NodeId, option: &str, value: &str) {
+        if let Some(Node {
+            kind: NodeKind::Shape { options, .. },
+            ..
+        }) = self.nodes.get_mut(&node)
+        {
+            options.insert(String::from(option), String::from(value));
+        }
+    }
+
+    /// Returns true if the node is a group.
+    pub fn is_group(&self, node: NodeId) -> bool {
+        matches!(
+            self.nodes.get(&node),
+            Some(Node {
+                kind: NodeKind::Group { .. },
+                ..
+            })
+        )
+    }
+
+    /// Returns the parent group of a node, or None for the root.
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.nodes.get(&node).and_then(|node| node.parent)
+    }
+
+    /// Removes a node, and all the nodes within it, from the scene.
+    /// The root cannot be removed.
+    pub fn remove(&mut self, node: NodeId) {
+        if node == self.root() || !self.nodes.contains_key(&node) {
+            return;
+        }
+        if let Some(parent) = self.parent(node) {
+            if let Some(parent) = self.nodes.get_mut(&parent) {
+                parent.children.retain(|child| *child != node);
+            }
+        }
+
+        let mut on_canvas = false;
+        let mut pending = vec![node];
+        while let Some(id) = pending.pop() {
+            if let Some(removed) = self.nodes.remove(&id) {
+                if let NodeKind::Shape {
+                    committed: Some(_), ..
+                } = removed.kind
+                {
+                    on_canvas = true;
+                }
+                pending.extend(removed.children);
+            }
+        }
+        if on_canvas {
+            self.deletions
+                .push(format!("{} delete {}", &self.canvas, self.tag(node)));
+        }
+    }
+
+    /// Moves a node above all its sibling nodes and their contents.
+    pub fn raise(&mut self, node: NodeId) {
+        if self.nodes.contains_key(&node) {
+            self.reorders
+                .push(format!("{} raise {}", &self.canvas, self.tag(node)));
+        }
+    }
+
+    /// Moves a node below all other items on the canvas.
+    pub fn lower(&mut self, node: NodeId) {
+        if self.nodes.contains_key(&node) {
+            self.reorders
+                .push(format!("{} lower {}", &self.canvas, self.tag(node)));
+        }
+    }
+
+    /// Returns the geometry of a shape.
+    pub fn shape(&self, node: NodeId) -> Option<&Shape> {
+        match self.nodes.get(&node) {
+            Some(Node {
+                kind: NodeKind::Shape { shape, .. },
+                ..
+            }) => Some(shape),
+            _ => None,
+        }
+    }
+
+    /// Changes the geometry of a shape.
+    pub fn set_shape(&mut self, node: NodeId, value: Shape) {
+        if let Some(Node {
+            kind: NodeKind::Shape { shape, .. },
+            ..
+        }) = self.nodes.get_mut(&node)
+        {
+            *shape = value;
+        }
+    }
+
+    /// Returns the transform of a group, relative to its parent.
+    pub fn transform(&self, node: NodeId) -> Option<Transform> {
+        match self.nodes.get(&node) {
+            Some(Node {
+                kind: NodeKind::Group { transform, .. },
+                ..
+            }) => Some(*transform),
+            _ => None,
+        }
+    }
+
+    /// Sets the transform of a group, relative to its parent.
+    pub fn set_transform(&mut self, node: NodeId, value: Transform) {
+        if let Some(Node {
+            kind: NodeKind::Group { transform, .. },
+            ..
+        }) = self.nodes.get_mut(&node)
+        {
+            *transform = value;
+        }
+    }
+
+    /// Returns the transform from a node's coordinates to canvas coordinates.
+    pub fn world_transform(&self, node: NodeId) -> Transform {
+        let mut result = Transform::identity();
+        let mut current = Some(node);
+        while let Some(id) = current {
+            if let Some(node) = self.nodes.get(&id) {
+                if let NodeKind::Group { transform, .. } = &node.kind {
+                    result = result.then(transform);
+                }
+                current = node.parent;
+            } else {
+                break;
+            }
+        }
+
+        result
+    }
+
+    /// Sends all changes made since the last commit to the canvas,
+    /// in a single message.
+    pub fn commit(&mut self) {
+        let changes = self.changes();
+        if !changes.is_empty() {
+            wish::tell_wish(&changes.join("\n"));
+        }
+    }
+
+    /// Returns the topmost shape at given canvas coordinates, if any.
+    ///
+    /// Only shapes which have been committed can be found.
+    pub fn hit_test(&self, x: f64, y: f64) -> Option<NodeId> {
+        self.find_overlapping((x, y), (x, y)).pop()
+    }
+
+    /// Returns the shapes overlapping the rectangle with given opposite
+    /// corners, in order from bottom to top.
+    ///
+    /// Only shapes which have been committed can be found.
+    pub fn find_overlapping(&self, (x1, y1): (f64, f64), (x2, y2): (f64, f64)) -> Vec<NodeId> {
+        let msg = format!(
+            "puts [lmap i [{} find overlapping {} {} {} {}] {{lsearch -inline -glob [{} gettags $i] {}n*}}] ; flush stdout",
+            &self.canvas, x1, y1, x2, y2, &self.canvas, &self.prefix
+        );
+        let result = wish::ask_wish(&msg);
+        self.parse_tags(&result)
+    }
+
+    // Converts a list of shape tags into ids of shapes in this scene.
+    fn parse_tags(&self, text: &str) -> Vec<NodeId> {
+        let shape_prefix = format!("{}n", &self.prefix);
+        text.split_whitespace()
+            .filter_map(|tag| tag.strip_prefix(&shape_prefix))
+            .filter_map(|id| id.parse::<u64>().ok())
+            .map(NodeId)
+            .filter(|id| self.nodes.contains_key(id))
+            .collect()
+    }
+
+    // Name of the canvas tag for a node.
+    fn tag(&self, node: NodeId) -> String {
+        if self.is_group(node) || node == self.root() {
+            format!("{}g{}", &self.prefix, node.0)
+        } else {
+            format!("{}n{}", &self.prefix, node.0)
+        }
+    }
+
+    // Tags for a shape: its own, and those of all its enclosing groups.
+    fn shape_tags(&self, node: NodeId) -> Vec<String> {
+        let mut tags = vec![self.tag(node)];
+        let mut current = self.parent(node);
+        while let Some(group) = current {
+            tags.push(self.tag(group));
+            current = self.parent(group);
+        }
+
+        tags
+    }
+
+    // Computes the tcl commands bringing the canvas up to date, and
+    // records the state they produce as committed.
+    fn changes(&mut self) -> Vec<String> {
+        let mut commands: Vec<String> = self.deletions.drain(..).collect();
+
+        // -- apply changed group transforms to the existing canvas items,
+        // parents before children
+        let mut groups = vec![self.root()];
+        while let Some(group) = groups.pop() {
+            let target = self.world_transform(group);
+            let current = match &self.nodes[&group].kind {
+                NodeKind::Group { canvas_world, .. } => *canvas_world,
+                NodeKind::Shape { .. } => None,
+            };
+            if let Some(delta) = current
+                .and_then(|current| current.inverse())
+                .map(|inverse| inverse.then(&target))
+            {
+                if delta.is_scaling() || delta.is_translating() {
+                    let tag = self.tag(group);
+                    if delta.is_scaling() {
+                        commands.push(format!(
+                            "{} scale {} 0 0 {} {}",
+                            &self.canvas, tag, delta.scale_x, delta.scale_y
+                        ));
+                    }
+                    if delta.is_translating() {
+                        commands.push(format!(
+                            "{} move {} {} {}",
+                            &self.canvas, tag, delta.dx, delta.dy
+                        ));
+                    }
+                    self.apply_to_descendants(group, &delta);
+                }
+            }
+            if let Some(Node {
+                kind: NodeKind::Group { canvas_world, .. },
+                children,
+                ..
+            }) = self.nodes.get_mut(&group)
+            {
+                *canvas_world = Some(target);
+                groups.extend(children.iter().rev());
+            }
+        }
+
+        // -- create or update shapes, in the order they appear in the tree
+        let mut shapes = vec![];
+        let mut pending = vec![self.root()];
+        while let Some(node) = pending.pop() {
+            if self.is_group(node) {
+                pending.extend(self.children(node).iter().rev());
+            } else {
+                shapes.push(node);
+            }
+        }
+        // -- shapes already on the canvas, which new shapes are stacked
+        // among
+        let stacked: Vec<bool> = shapes
+            .iter()
+            .map(|node| {
+                matches!(
+                    self.nodes[node].kind,
+                    NodeKind::Shape {
+                        committed: Some(_),
+                        ..
+                    }
+                )
+            })
+            .collect();
+        let mut previous: Option<String> = None;
+        for (index, node) in shapes.iter().copied().enumerate() {
+            let next_stacked = stacked[index + 1..]
+                .iter()
+                .position(|is_stacked| *is_stacked)
+                .map(|position| self.tag(shapes[index + 1 + position]));
+            let world = self.world_transform(node);
+            let tags = self.shape_tags(node);
+            let tag = self.tag(node);
+            if let Some(Node {
+                kind:
+                    NodeKind::Shape {
+                        shape,
+                        options,
+                        committed,
+                    },
+                ..
+            }) = self.nodes.get_mut(&node)
+            {
+                let points: Vec<(f64, f64)> =
+                    shape.points().iter().map(|p| world.apply(*p)).collect();
+                let mut desired = options.clone();
+                if let Shape::Text(_, text) = shape {
+                    desired.insert(String::from("text"), text.clone());
+                }
+                let coords = points_string(&points);
+
+                match committed {
+                    Some(current) if current.kind == shape.kind() => {
+                        if !close_points(&current.points, &points) {
+                            commands.push(format!("{} coords {} {}", &self.canvas, tag, coords));
+                        }
+                        let mut changed = String::new();
+                        for (option, value) in &desired {
+                            if current.options.get(option) != Some(value) {
+                                changed.push_str(&format!(
+                                    "-{} {} ",
+                                    option,
+                                    wish::tcl_quote(value)
+                                ));
+                            }
+                        }
+                        if !changed.is_empty() {
+                            commands.push(format!(
+                                "{} itemconfigure {} {}",
+                                &self.canvas, tag, changed
+                            ));
+                        }
+                    }
+                    _ => {
+                        if committed.is_some() {
+                            commands.push(format!("{} delete {}", &self.canvas, tag));
+                        }
+                        let mut msg = format!(
+                            "{} create {} {} -tags {{{}}} ",
+                            &self.canvas,
+                            shape.kind(),
+                            coords,
+                            tags.join(" ")
+                        );
+                        for (option, value) in &desired {
+                            msg.push_str(&format!("-{} {} ", option, wish::tcl_quote(value)));
+                        }
+                        commands.push(msg);
+                        // -- created on top: move below any shapes which
+                        // follow it in the tree
+                        if let Some(next) = &next_stacked {
+                            commands.push(match &previous {
+                                Some(previous) => {
+                                    format!("{} raise {} {}", &self.canvas, tag, previous)
+                                }
+                                None => format!("{} lower {} {}", &self.canvas, tag, next),
+                            });
+                        }
+                    }
+                }
+
+                *committed = Some(Committed {
+                    kind: String::from(shape.kind()),
+                    points,
+                    options: desired,
+                });
+            }
+            previous = Some(tag);
+        }
+
+        commands.append(&mut self.reorders);
+        commands
+    }
+
+    // Records that the canvas items within a group have been transformed.
+    fn apply_to_descendants(&mut self, group: NodeId, delta: &Transform) {
+        let mut pending = self.children(group);
+        while let Some(node) = pending.pop() {
+            pending.extend(self.children(node));
+            if let Some(node) = self.nodes.get_mut(&node) {
+                match &mut node.kind {
+                    NodeKind::Group { canvas_world, .. } => {
+                        if let Some(world) = canvas_world {
+                            *world = world.then(delta);
+                        }
+                    }
+                    NodeKind::Shape {
+                        committed: Some(committed),
+                        ..
+                    } => {
+                        for point in committed.points.iter_mut() {
+                            *point = delta.apply(*point);
+                        }
+                    }
+                    NodeKind::Shape { .. } => {}
+                }
+            }
+        }
+    }
+}
+
+fn points_string(points: &[(f64, f64)]) -> String {
+    let mut result = String::new();
+    for (x, y) in points {
+        result.push_str(&format!("{} {} ", x, y));
+    }
+
+    result.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transform_then() {
+        let t = Transform::scale(2.0, 3.0).then(&Transform::translate(10.0, 20.0));
+        assert_eq!((12.0, 23.0), t.apply((1.0, 1.0)));
+        let inverse = t.inverse().unwrap();
+        assert!(close_points(&[(1.0, 1.0)], &[inverse.apply((12.0, 23.0))]));
+        assert!(Transform::scale(0.0, 1.0).inverse().is_none());
+    }
+
+    #[test]
+    fn commit_creates_once() {
+        let mut scene = Scene::with_prefix(".c", "s");
+        let group = scene.add_group(scene.root());
+        let rect = scene.add_shape(group, Shape::Rectangle((0.0, 0.0), (10.0, 5.0)));
+        scene.configure(rect, "fill", "red");
+
+        let changes = scene.changes();
+        assert_eq!(
+            vec![".c create rectangle 0 0 10 5 -tags {sn2 sg1 sg0} -fill red "],
+            changes
+        );
+        assert!(scene.changes().is_empty());
+    }
+
+    #[test]
+    fn commit_moves_group() {
+        let mut scene = Scene::with_prefix(".c", "s");
+        let group = scene.add_group(scene.root());
+        scene.add_shape(group, Shape::Line(vec![(0.0, 0.0), (10.0, 10.0)]));
+        scene.add_shape(group, Shape::Text((5.0, 5.0), String::from("a b")));
+        scene.changes();
+
+        scene.set_transform(group, Transform::translate(3.0, 4.0));
+        assert_eq!(vec![".c move sg1 3 4"], scene.changes());
+
+        scene.set_transform(group, Transform::scale(2.0, 2.0));
+        assert_eq!(
+            vec![".c scale sg1 0 0 2 2", ".c move sg1 -6 -8"],
+            scene.changes()
+        );
+        assert!(scene.changes().is_empty());
+    }
+
+    #[test]
+    fn commit_updates_and_removes() {
+        let mut scene = Scene::with_prefix(".c", "s");
+        let oval = scene.add_shape(scene.root(), Shape::Oval((0.0, 0.0), (4.0, 4.0)));
+        scene.changes();
+
+        scene.set_shape(oval, Shape::Oval((1.0, 1.0), (4.0, 4.0)));
+        scene.configure(oval, "outline", "blue");
+        assert_eq!(
+            vec![
+                ".c coords sn1 1 1 4 4",
+                ".c itemconfigure sn1 -outline blue "
+            ],
+            scene.changes()
+        );
+
+        scene.remove(oval);
+        assert_eq!(vec![".c delete sn1"], scene.changes());
+        assert_eq!(None, scene.shape(oval));
+    }
+
+    #[test]
+    fn commit_quotes_options() {
+        let mut scene = Scene::with_prefix(".c", "s");
+        let text = scene.add_shape(scene.root(), Shape::Text((0.0, 0.0), String::from("a}b")));
+        scene.configure(text, "font", "Courier 10");
+        assert_eq!(
+            vec![".c create text 0 0 -tags {sn1 sg0} -font Courier\\ 10 -text a\\}b "],
+            scene.changes()
+        );
+
+        scene.set_shape(text, Shape::Text((0.0, 0.0), String::from("ends \\")));
+        assert_eq!(
+            vec![".c itemconfigure sn1 -text ends\\ \\\\ "],
+            scene.changes()
+        );
+    }
+
+    #[test]
+    fn commit_stacks_in_tree_order() {
+        let mut scene = Scene::with_prefix(".c", "s");
+        let empty = scene.add_group(scene.root());
+        let group = scene.add_group(scene.root());
+        scene.add_shape(group, Shape::Line(vec![(0.0, 0.0), (1.0, 1.0)]));
+        scene.add_shape(scene.root(), Shape::Line(vec![(0.0, 0.0), (2.0, 2.0)]));
+        scene.changes();
+
+        // -- added to the group, so below the shape after the group
+        scene.add_shape(group, Shape::Line(vec![(0.0, 0.0), (3.0, 3.0)]));
+        assert_eq!(
+            vec![
+                ".c create line 0 0 3 3 -tags {sn5 sg2 sg0} ",
+                ".c raise sn5 sn3"
+            ],
+            scene.changes()
+        );
+
+        // -- first in the tree, so below all other shapes
+        scene.add_shape(empty, Shape::Line(vec![(0.0, 0.0), (4.0, 4.0)]));
+        assert_eq!(
+            vec![
+                ".c create line 0 0 4 4 -tags {sn6 sg1 sg0} ",
+                ".c lower sn6 sn3"
+            ],
+            scene.changes()
+        );
+
+        // -- last in the tree, so left on top
+        scene.add_shape(scene.root(), Shape::Line(vec![(0.0, 0.0), (5.0, 5.0)]));
+        assert_eq!(
+            vec![".c create line 0 0 5 5 -tags {sn7 sg0} "],
+            scene.changes()
+        );
+    }
+
+    #[test]
+    fn parse_hit_tags() {
+        let mut scene = Scene::with_prefix(".c", "s");
+        let rect = scene.add_shape(scene.root(), Shape::Rectangle((0.0, 0.0), (1.0, 1.0)));
+        assert_eq!(vec![rect], scene.parse_tags("sn1 {} sn99 other"));
+    }
+}
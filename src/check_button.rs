@@ -38,6 +38,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -66,6 +67,7 @@ impl widget::TkWidget for TkCheckButton {
 }
 impl grid::TkGridLayout for TkCheckButton {}
 impl pack::TkPackLayout for TkCheckButton {}
+impl place::TkPlaceLayout for TkCheckButton {}
 impl widget::TkLabelOptions for TkCheckButton {}
 
 impl TkCheckButton {
@@ -9,6 +9,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -44,6 +45,7 @@ impl widget::TkWidget for TkCombobox {
 }
 impl grid::TkGridLayout for TkCombobox {}
 impl pack::TkPackLayout for TkCombobox {}
+impl place::TkPlaceLayout for TkCombobox {}
 
 impl TkCombobox {
     /// Sets the height of the widget, in rows
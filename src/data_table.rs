@@ -0,0 +1,472 @@
+//! Data table - a table showing many rows of data held in Rust.
+//!
+//! A treeview holds an item for every row it shows, and filling it costs
+//! several commands sent to Tk per row. A data table instead reads its rows
+//! from a [TableModel], and only has items for the rows which fit in the
+//! view: as the table is scrolled, the same items are given the values of
+//! the rows now visible. Sorting and filtering are done in Rust, and each
+//! redraw is sent to Tk as a single command.
+//!
+//! ```ignore
+//! let rows: Vec<Vec<String>> = (0..100_000)
+//!     .map(|i| vec![format!("file{}", i), (i * 7 % 1000).to_string()])
+//!     .collect();
+//! let model = Arc::new(Mutex::new(rows));
+//! let table = rish::make_data_table(&root, &["Name", "Size"], model.clone());
+//! table.grid().sticky(rish::Sticky::NESW).layout();
+//!
+//! // -- after changing the rows, tell the table to show them
+//! model.lock().unwrap().push(vec![String::from("new"), String::from("1")]);
+//! table.model_changed(TableChange::Reset);
+//! ```
+//!
+//! Clicking a column heading sorts the rows by that column, and clicking it
+//! again reverses the order. Rows are referred to by their position in the
+//! model, whatever order they are shown in.
+//!
+
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use super::grid;
+use super::pack;
+use super::place;
+use super::treeview;
+use super::widget;
+use super::wish;
+
+/// Source of the rows shown in a [TkDataTable].
+///
+/// The table is not told when the model changes: after changing the rows,
+/// call [model_changed](TkDataTable::model_changed) on each table showing
+/// the model, or the tables keep showing, sorting and filtering the rows
+/// as they were.
+pub trait TableModel: Send {
+    /// Returns the number of rows.
+    fn row_count(&self) -> usize;
+
+    /// Returns the text to show for the given row and column.
+    fn cell(&self, row: usize, column: usize) -> String;
+}
+
+/// A list of rows, each a list of cells, can be shown directly.
+impl TableModel for Vec<Vec<String>> {
+    fn row_count(&self) -> usize {
+        self.len()
+    }
+
+    fn cell(&self, row: usize, column: usize) -> String {
+        self.get(row)
+            .and_then(|cells| cells.get(column))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Describes a change made to a table's model, passed to
+/// [model_changed](TkDataTable::model_changed).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TableChange {
+    /// rows were added, removed or reordered
+    Reset,
+    /// values changed in the given rows
+    Rows(Range<usize>),
+}
+
+type RowFilter = Box<dyn Fn(&dyn TableModel, usize) -> bool + Send + 'static>;
+
+struct TableState {
+    columns: Vec<String>,
+    filter: Option<RowFilter>,
+    items: usize,
+    model: Arc<Mutex<dyn TableModel>>,
+    order: Vec<usize>,
+    page: usize,
+    selected: BTreeSet<usize>,
+    sort: Option<(usize, bool)>,
+    top: usize,
+}
+
+impl TableState {
+    // Recomputes which model rows are shown, and in what order.
+    fn reorder(&mut self) {
+        let model = self.model.lock().unwrap();
+        self.order = view_order(&*model, self.filter.as_ref(), self.sort);
+        self.top = self.top.min(self.order.len().saturating_sub(self.page));
+    }
+
+    // Returns a tcl script showing the rows from top in treeview `tv`,
+    // creating items as needed and detaching those not used.
+    fn redraw_script(&mut self, tv: &str, scrollbar: &str) -> String {
+        let model = self.model.lock().unwrap();
+        let rows = &self.order[self.top.min(self.order.len())..];
+        let visible = rows.len().min(self.page);
+        let mut script = vec![];
+        let mut selection = vec![];
+
+        for (slot, row) in rows.iter().take(visible).enumerate() {
+            if slot >= self.items {
+                script.push(format!("{} insert {{}} end -id row{}", tv, slot));
+            }
+            let cells: Vec<String> = (0..self.columns.len())
+                .map(|column| wish::tcl_quote(&model.cell(*row, column)))
+                .collect();
+            script.push(format!(
+                "{} item row{} -values [list {}]",
+                tv,
+                slot,
+                cells.join(" ")
+            ));
+            script.push(format!("{} move row{} {{}} {}", tv, slot, slot));
+            if self.selected.contains(row) {
+                selection.push(format!("row{}", slot));
+            }
+        }
+        for slot in visible..self.items {
+            script.push(format!("{} detach row{}", tv, slot));
+        }
+        self.items = self.items.max(visible);
+        script.push(format!(
+            "{} selection set [list {}]",
+            tv,
+            selection.join(" ")
+        ));
+
+        // -- state used by rish_table_scroll, and the scrollbar's position
+        let total = self.order.len();
+        let (first, last) = if total == 0 {
+            (0.0, 1.0)
+        } else {
+            (
+                self.top as f64 / total as f64,
+                (self.top + visible) as f64 / total as f64,
+            )
+        };
+        script.push(format!(
+            "set rish_table({0},rows) {1} ; set rish_table({0},top) {2} ; set rish_table({0},page) {3}",
+            tv, total, self.top, self.page
+        ));
+        script.push(format!("{} set {} {}", scrollbar, first, last));
+
+        script.join(" ; ")
+    }
+}
+
+// Returns the model rows passing the filter, sorted by the given column and
+// direction: numerically if every cell in the column is a number.
+fn view_order(
+    model: &dyn TableModel,
+    filter: Option<&RowFilter>,
+    sort: Option<(usize, bool)>,
+) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..model.row_count())
+        .filter(|row| filter.is_none_or(|filter| filter(model, *row)))
+        .collect();
+
+    if let Some((column, decreasing)) = sort {
+        let cells: Vec<String> = order.iter().map(|row| model.cell(*row, column)).collect();
+        let numbers: Option<Vec<f64>> = cells.iter().map(|cell| cell.parse::<f64>().ok()).collect();
+        let mut keyed: Vec<(usize, usize)> = order.iter().copied().enumerate().collect();
+        let compare = |a: usize, b: usize| match &numbers {
+            Some(numbers) => numbers[a].total_cmp(&numbers[b]),
+            None => cells[a]
+                .to_lowercase()
+                .cmp(&cells[b].to_lowercase())
+                .then_with(|| cells[a].cmp(&cells[b])),
+        };
+        keyed.sort_by(|(a, _), (b, _)| {
+            let ordering: Ordering = compare(*a, *b);
+            if decreasing {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        order = keyed.into_iter().map(|(_, row)| row).collect();
+    }
+
+    order
+}
+
+/// Refers to a data table
+#[derive(Clone)]
+pub struct TkDataTable {
+    pub id: String,
+    treeview: treeview::TkTreeview,
+    scrollbar: String,
+    state: Arc<Mutex<TableState>>,
+}
+
+/// Creates an instance of a data table in given parent, showing the rows of
+/// model under the given column headings. The table asks for room for 20
+/// rows, and shows as many rows as fit in its actual height.
+pub fn make_data_table(
+    parent: &impl widget::TkWidget,
+    columns: &[&str],
+    model: Arc<Mutex<dyn TableModel>>,
+) -> TkDataTable {
+    let id = wish::next_wid(parent.id());
+    let tv = format!("{}.table", id);
+    let scrollbar = format!("{}.scrollbar", id);
+    let key = wish::callback_key(&tv, "scroll");
+    let page_key = wish::callback_key(&tv, "page");
+    let names: Vec<String> = (0..columns.len()).map(|i| format!("c{}", i)).collect();
+
+    let msg = format!(
+        "ttk::frame {0} ; \
+         ttk::treeview {1} -columns {{{2}}} -show headings -height 20 ; \
+         ttk::scrollbar {3} -orient vertical -command {{rish_table_scroll {1} {4}}} ; \
+         grid {1} -row 0 -column 0 -sticky nsew ; \
+         grid {3} -row 0 -column 1 -sticky ns ; \
+         grid columnconfigure {0} 0 -weight 1 ; \
+         grid rowconfigure {0} 0 -weight 1 ; \
+         bind {1} <MouseWheel> {{ rish_table_scroll {1} {4} scroll [expr {{%D > 0 ? -3 : 3}}] units ; break }} ; \
+         bind {1} <Button-4> {{ rish_table_scroll {1} {4} scroll -3 units ; break }} ; \
+         bind {1} <Button-5> {{ rish_table_scroll {1} {4} scroll 3 units ; break }} ; \
+         bind {1} <Configure> {{+puts cb1f-{5}-[rish_table_page %W %h] ; flush stdout}}",
+        id,
+        tv,
+        names.join(" "),
+        scrollbar,
+        key,
+        page_key
+    );
+    wish::tell_wish(&msg);
+
+    let table = TkDataTable {
+        id,
+        treeview: treeview::TkTreeview { id: tv },
+        scrollbar,
+        state: Arc::new(Mutex::new(TableState {
+            columns: columns.iter().map(|column| String::from(*column)).collect(),
+            filter: None,
+            items: 0,
+            model,
+            order: vec![],
+            page: 20,
+            selected: BTreeSet::new(),
+            sort: None,
+            top: 0,
+        })),
+    };
+
+    for (column, name) in names.iter().enumerate() {
+        let sorter = table.clone();
+        table.treeview.heading_command(name, move || {
+            let decreasing = sorter.state.lock().unwrap().sort == Some((column, false));
+            sorter.sort(column, decreasing);
+        });
+    }
+
+    let scroller = table.clone();
+    wish::add_callback1_float(
+        &key,
+        wish::mk_callback1_float(move |top| scroller.scroll_to(top as usize)),
+    );
+
+    // -- the rows shown follow the treeview's height
+    let pager = table.clone();
+    wish::add_callback1_float(
+        &page_key,
+        wish::mk_callback1_float(move |rows| {
+            let mut state = pager.state.lock().unwrap();
+            if state.page != rows as usize {
+                state.page = rows as usize;
+                state.reorder();
+                pager.redraw(&mut state);
+            }
+        }),
+    );
+
+    let selector = table.clone();
+    table.treeview.on_select(move |items| {
+        let mut state = selector.state.lock().unwrap();
+        let top = state.top.min(state.order.len());
+        let shown: Vec<usize> = state.order[top..]
+            .iter()
+            .take(state.page)
+            .copied()
+            .collect();
+        for row in &shown {
+            state.selected.remove(row);
+        }
+        for item in items {
+            if let Some(slot) = item
+                .id
+                .strip_prefix("row")
+                .and_then(|slot| slot.parse::<usize>().ok())
+            {
+                if let Some(row) = shown.get(slot) {
+                    state.selected.insert(*row);
+                }
+            }
+        }
+    });
+
+    table.update_headings(&table.state.lock().unwrap());
+    table.model_changed(TableChange::Reset);
+
+    table
+}
+
+impl widget::TkWidget for TkDataTable {
+    /// Returns the widget's id reference - used within tk
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl grid::TkGridLayout for TkDataTable {}
+impl pack::TkPackLayout for TkDataTable {}
+impl place::TkPlaceLayout for TkDataTable {}
+
+impl TkDataTable {
+    /// Shows all rows again, after a [filter](TkDataTable::filter).
+    pub fn clear_filter(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.filter = None;
+        state.reorder();
+        self.redraw(&mut state);
+    }
+
+    /// Shows the rows in model order again, after a
+    /// [sort](TkDataTable::sort).
+    pub fn clear_sort(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.sort = None;
+        state.reorder();
+        self.update_headings(&state);
+        self.redraw(&mut state);
+    }
+
+    /// Sets the width in pixels for the given column.
+    pub fn column_width(&self, column: usize, value: u64) {
+        self.treeview.column_width(&format!("c{}", column), value);
+    }
+
+    /// Only shows rows for which the predicate, given the model and a row,
+    /// returns true.
+    pub fn filter(&self, predicate: impl Fn(&dyn TableModel, usize) -> bool + Send + 'static) {
+        let mut state = self.state.lock().unwrap();
+        state.filter = Some(Box::new(predicate));
+        state.reorder();
+        self.redraw(&mut state);
+    }
+
+    /// Sets the number of rows the table asks room for: the rows shown
+    /// follow the height it is then given.
+    pub fn height(&self, rows: u64) {
+        widget::configure(&self.treeview.id, "height", &rows.to_string());
+        let mut state = self.state.lock().unwrap();
+        state.page = rows as usize;
+        state.reorder();
+        self.redraw(&mut state);
+    }
+
+    /// Updates the view after the model has changed: with
+    /// [Reset](TableChange::Reset) the rows are filtered and sorted again;
+    /// with [Rows](TableChange::Rows) only when the table is filtered or
+    /// sorted.
+    pub fn model_changed(&self, change: TableChange) {
+        let mut state = self.state.lock().unwrap();
+        let reorder = match change {
+            TableChange::Reset => true,
+            TableChange::Rows(_) => state.filter.is_some() || state.sort.is_some(),
+        };
+        if reorder {
+            let count = state.model.lock().unwrap().row_count();
+            state.selected.retain(|row| *row < count);
+            state.reorder();
+        }
+        self.redraw(&mut state);
+    }
+
+    /// Returns the number of rows shown, after filtering.
+    pub fn row_count(&self) -> usize {
+        self.state.lock().unwrap().order.len()
+    }
+
+    /// Scrolls the view so the given position, in the filtered and sorted
+    /// rows, is at the top.
+    pub fn scroll_to(&self, position: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.top = position.min(state.order.len().saturating_sub(state.page));
+        self.redraw(&mut state);
+    }
+
+    /// Returns the selected rows, by their position in the model.
+    pub fn selected_rows(&self) -> Vec<usize> {
+        self.state
+            .lock()
+            .unwrap()
+            .selected
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Sets the selected rows, by their position in the model.
+    pub fn set_selected_rows(&self, rows: &[usize]) {
+        let mut state = self.state.lock().unwrap();
+        state.selected = rows.iter().copied().collect();
+        self.redraw(&mut state);
+    }
+
+    /// Sorts the rows by the given column, numerically if every value in
+    /// the column is a number.
+    pub fn sort(&self, column: usize, decreasing: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.sort = Some((column, decreasing));
+        state.reorder();
+        self.update_headings(&state);
+        self.redraw(&mut state);
+    }
+
+    fn redraw(&self, state: &mut TableState) {
+        let msg = state.redraw_script(&self.treeview.id, &self.scrollbar);
+        wish::tell_wish(&msg);
+    }
+
+    // Sets the headings, marking the sorted column with its direction.
+    fn update_headings(&self, state: &TableState) {
+        for (column, name) in state.columns.iter().enumerate() {
+            let title = match state.sort {
+                Some((sorted, decreasing)) if sorted == column => {
+                    format!("{} {}", name, if decreasing { '▼' } else { '▲' })
+                }
+                _ => name.clone(),
+            };
+            self.treeview.heading_text(&format!("c{}", column), &title);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> Vec<Vec<String>> {
+        [["b", "10"], ["a", "9"], ["C", "10.5"], ["d", "x"]]
+            .iter()
+            .map(|row| row.iter().map(|cell| String::from(*cell)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn table_view_order() {
+        let model = model();
+        assert_eq!(vec![0, 1, 2, 3], view_order(&model, None, None));
+        assert_eq!(vec![1, 0, 2, 3], view_order(&model, None, Some((0, false))));
+        assert_eq!(vec![3, 2, 0, 1], view_order(&model, None, Some((0, true))));
+
+        // -- numeric only when every cell is a number
+        let filter: RowFilter = Box::new(|model, row| model.cell(row, 1) != "x");
+        assert_eq!(
+            vec![1, 0, 2],
+            view_order(&model, Some(&filter), Some((1, false)))
+        );
+        assert_eq!(vec![0, 2, 1, 3], view_order(&model, None, Some((1, false))));
+    }
+}
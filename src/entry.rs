@@ -4,6 +4,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -33,6 +34,7 @@ impl widget::TkWidget for TkEntry {
 
 impl grid::TkGridLayout for TkEntry {}
 impl pack::TkPackLayout for TkEntry {}
+impl place::TkPlaceLayout for TkEntry {}
 
 impl TkEntry {
     /// Specifies the font to use for text.
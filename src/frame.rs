@@ -4,6 +4,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -30,6 +31,7 @@ impl widget::TkWidget for TkFrame {
 }
 impl grid::TkGridLayout for TkFrame {}
 impl pack::TkPackLayout for TkFrame {}
+impl place::TkPlaceLayout for TkFrame {}
 
 impl TkFrame {
     /// Size of border around frame
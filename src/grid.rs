@@ -29,6 +29,18 @@
 //!     .layout();                  // completes the layout
 //! ```
 //!
+//! ## Rows and columns
+//!
+//! How the rows and columns of a container share out its space is set in
+//! the same builder style, using a [GridConfig]:
+//!
+//! ```ignore
+//! frame.grid_column(1)            // column 1 of the frame's grid
+//!     .weight(1)                  // takes any extra width
+//!     .uniform("fields")          // same width as other "fields" columns
+//!     .configure();               // applies the settings
+//! ```
+//!
 
 use super::widget;
 use super::wish;
@@ -38,6 +50,10 @@ use super::wish;
 /// Apart from `layout`, the methods on this struct set the values of different
 /// options in a builder style: call `layout` to finish the layout.
 ///
+/// The settings of a widget already laid out are returned by
+/// [grid_info](TkGridLayout::grid_info), and can be read with the `_get`
+/// methods.
+///
 #[derive(Clone, Debug)]
 pub struct GridLayout {
     id: String,
@@ -68,54 +84,122 @@ impl GridLayout {
         }
     }
 
+    // Creates the settings described by the option-value list returned by
+    // "grid info". Padding given as a pair of values is read as the first.
+    pub(super) fn from_info(wid: &str, info: &str) -> GridLayout {
+        let mut layout = GridLayout::new(wid);
+        let parts = wish::split_list(info);
+
+        for pair in parts.chunks_exact(2) {
+            let value = pair[1]
+                .split_whitespace()
+                .next()
+                .and_then(|value| value.parse::<u64>().ok());
+            match pair[0].as_str() {
+                "-column" => layout.column = value,
+                "-columnspan" => layout.column_span = value,
+                "-ipadx" => layout.ipadx = value,
+                "-ipady" => layout.ipady = value,
+                "-padx" => layout.padx = value,
+                "-pady" => layout.pady = value,
+                "-row" => layout.row = value,
+                "-rowspan" => layout.row_span = value,
+                "-sticky" => layout.sticky = widget::Sticky::from_name(&pair[1]),
+                _ => {}
+            }
+        }
+
+        layout
+    }
+
     /// Specifies the (0-indexed) column in which to place this widget.
     pub fn column(&mut self, column: u64) -> &mut Self {
         self.column = Some(column);
         self
     }
 
+    /// Returns the column in which this widget is placed, if set.
+    pub fn column_get(&self) -> Option<u64> {
+        self.column
+    }
+
     /// The number of columns this widget should span.
     pub fn column_span(&mut self, span: u64) -> &mut Self {
         self.column_span = Some(span);
         self
     }
 
+    /// Returns the number of columns this widget spans, if set.
+    pub fn column_span_get(&self) -> Option<u64> {
+        self.column_span
+    }
+
     /// Horizontal padding (inside content border).
     pub fn ipadx(&mut self, pad: u64) -> &mut Self {
         self.ipadx = Some(pad);
         self
     }
 
+    /// Returns the horizontal padding inside the border, if set.
+    pub fn ipadx_get(&self) -> Option<u64> {
+        self.ipadx
+    }
+
     /// Vertical padding (inside content border).
     pub fn ipady(&mut self, pad: u64) -> &mut Self {
         self.ipady = Some(pad);
         self
     }
 
+    /// Returns the vertical padding inside the border, if set.
+    pub fn ipady_get(&self) -> Option<u64> {
+        self.ipady
+    }
+
     /// Horizontal padding (outside content border).
     pub fn padx(&mut self, pad: u64) -> &mut Self {
         self.padx = Some(pad);
         self
     }
 
+    /// Returns the horizontal padding outside the border, if set.
+    pub fn padx_get(&self) -> Option<u64> {
+        self.padx
+    }
+
     /// Vertical padding (outside content border).
     pub fn pady(&mut self, pad: u64) -> &mut Self {
         self.pady = Some(pad);
         self
     }
 
+    /// Returns the vertical padding outside the border, if set.
+    pub fn pady_get(&self) -> Option<u64> {
+        self.pady
+    }
+
     /// Specifies the (0-indexed) row in which to place this widget.
     pub fn row(&mut self, row: u64) -> &mut Self {
         self.row = Some(row);
         self
     }
 
+    /// Returns the row in which this widget is placed, if set.
+    pub fn row_get(&self) -> Option<u64> {
+        self.row
+    }
+
     /// Number of rows this widget should span.
     pub fn row_span(&mut self, span: u64) -> &mut Self {
         self.row_span = Some(span);
         self
     }
 
+    /// Returns the number of rows this widget spans, if set.
+    pub fn row_span_get(&self) -> Option<u64> {
+        self.row_span
+    }
+
     /// When a widget is smaller than its containing space, this
     /// setting controls how the widget is expanded or positioned
     /// within that space.
@@ -124,6 +208,11 @@ impl GridLayout {
         self
     }
 
+    /// Returns the sides of its space to which this widget sticks.
+    pub fn sticky_get(&self) -> widget::Sticky {
+        self.sticky.clone()
+    }
+
     /// Called last to finally create the layout with the parameter values
     /// set up by the builder.
     pub fn layout(&self) {
@@ -161,6 +250,71 @@ impl GridLayout {
     }
 }
 
+/// Refers to the settings for a column or row of a container's grid.
+///
+/// Apart from `configure`, the methods on this struct set the values of
+/// different options in a builder style: call `configure` to apply them.
+/// Options not set are left unchanged.
+///
+#[derive(Clone, Debug)]
+pub struct GridConfig {
+    command: &'static str,
+    id: String,
+    index: u64,
+    options: widget::OptionList,
+}
+
+impl GridConfig {
+    // Creates settings applied by the given grid command: "columnconfigure"
+    // or "rowconfigure".
+    pub(super) fn new(command: &'static str, wid: &str, index: u64) -> GridConfig {
+        GridConfig {
+            command,
+            id: String::from(wid),
+            index,
+            options: widget::OptionList::default(),
+        }
+    }
+
+    /// Minimum width of the column, or height of the row, in pixels.
+    pub fn minsize(&mut self, size: u64) -> &mut Self {
+        self.options.set("minsize", &size.to_string());
+        self
+    }
+
+    /// Padding added to the largest widget in the column or row, in pixels.
+    pub fn pad(&mut self, pad: u64) -> &mut Self {
+        self.options.set("pad", &pad.to_string());
+        self
+    }
+
+    /// Names a group of columns, or rows, which are kept the same size, in
+    /// proportion to their weights.
+    pub fn uniform(&mut self, group: &str) -> &mut Self {
+        self.options.set("uniform", group);
+        self
+    }
+
+    /// Share of any extra space given to this column or row: 0 (the
+    /// default) keeps it at its requested size.
+    pub fn weight(&mut self, weight: u64) -> &mut Self {
+        self.options.set("weight", &weight.to_string());
+        self
+    }
+
+    /// Called last to apply the settings.
+    pub fn configure(&self) {
+        if self.options.is_empty() {
+            return;
+        }
+        let msg = format!(
+            "grid {} {} {} {}",
+            self.command, self.id, self.index, self.options
+        );
+        wish::tell_wish(&msg);
+    }
+}
+
 /// Common functions for widgets that can be arranged using GridLayouts
 pub trait TkGridLayout: widget::TkWidget {
     /// Creates a GridLayout instance for placing this widget within its parent.
@@ -188,4 +342,41 @@ pub trait TkGridLayout: widget::TkWidget {
         let msg = format!("grid forget {}", self.id());
         wish::tell_wish(&msg);
     }
+
+    /// Returns the current layout settings of this widget.
+    fn grid_info(&self) -> GridLayout {
+        let msg = format!("puts [grid info {}] ; flush stdout", self.id());
+        let result = wish::ask_wish(&msg);
+        GridLayout::from_info(self.id(), &result)
+    }
+
+    /// Removes this widget from layout, remembering its settings: a later
+    /// `grid().layout()` puts it back in the same place.
+    fn grid_remove(&self) {
+        let msg = format!("grid remove {}", self.id());
+        wish::tell_wish(&msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_info_parse() {
+        let layout = GridLayout::from_info(
+            ".r2",
+            "-in .r1 -column 2 -row 1 -columnspan 3 -rowspan 1 -ipadx 0 -ipady 0 -padx {5 10} -pady 4 -sticky ew",
+        );
+        assert_eq!(Some(2), layout.column_get());
+        assert_eq!(Some(1), layout.row_get());
+        assert_eq!(Some(3), layout.column_span_get());
+        assert_eq!(Some(5), layout.padx_get());
+        assert_eq!(Some(4), layout.pady_get());
+        assert_eq!(widget::Sticky::EW, layout.sticky_get());
+        assert_eq!(
+            widget::Sticky::None,
+            GridLayout::from_info(".r2", "").sticky_get()
+        );
+    }
 }
@@ -1,8 +1,38 @@
 //! Images - method to read in an image from file.
 //!
 
+use std::fmt;
+
 use super::wish;
 
+/// File formats in which images can be written.
+///
+/// Tk supports gif, png and ppm directly: the other formats need the
+/// [Img](https://sourceforge.net/projects/tkimg/) package (tkimg).
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageFormat {
+    Bmp,
+    Gif,
+    Jpeg,
+    Png,
+    Ppm,
+    Tiff,
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            ImageFormat::Bmp => "bmp",
+            ImageFormat::Gif => "gif",
+            ImageFormat::Jpeg => "jpeg",
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Tiff => "tiff",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
 /// Refers to an image
 #[derive(Clone, Debug, PartialEq)]
 pub struct TkImage {
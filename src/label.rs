@@ -37,6 +37,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -63,6 +64,7 @@ impl widget::TkWidget for TkLabel {
 }
 impl grid::TkGridLayout for TkLabel {}
 impl pack::TkPackLayout for TkLabel {}
+impl place::TkPlaceLayout for TkLabel {}
 impl widget::TkLabelOptions for TkLabel {}
 
 impl TkLabel {
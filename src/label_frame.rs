@@ -5,6 +5,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -31,6 +32,7 @@ impl widget::TkWidget for TkLabelFrame {
 }
 impl grid::TkGridLayout for TkLabelFrame {}
 impl pack::TkPackLayout for TkLabelFrame {}
+impl place::TkPlaceLayout for TkLabelFrame {}
 
 impl TkLabelFrame {
     /// Size of border around frame
@@ -62,6 +62,9 @@ pub use check_button::*;
 pub mod combobox;
 pub use combobox::*;
 
+pub mod data_table;
+pub use data_table::*;
+
 pub mod dialog;
 pub use dialog::*;
 
@@ -89,6 +92,9 @@ pub use label_frame::*;
 pub mod listbox;
 pub use listbox::*;
 
+pub mod log_view;
+pub use log_view::*;
+
 pub mod menu;
 pub use menu::*;
 
@@ -101,6 +107,9 @@ pub use pack::*;
 pub mod paned_window;
 pub use paned_window::*;
 
+pub mod place;
+pub use place::*;
+
 pub mod progressbar;
 pub use progressbar::*;
 
@@ -116,6 +125,9 @@ pub use scrollbar::*;
 pub mod separator;
 pub use separator::*;
 
+pub mod session;
+pub use session::*;
+
 pub mod spinbox;
 pub use spinbox::*;
 
@@ -131,6 +143,14 @@ pub use toplevel::*;
 pub mod treeview;
 pub use treeview::*;
 
+pub mod ui;
+pub use ui::*;
+
+#[cfg(feature = "ui_loader")]
+pub mod ui_loader;
+#[cfg(feature = "ui_loader")]
+pub use ui_loader::*;
+
 pub mod widget;
 pub use widget::*;
 
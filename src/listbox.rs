@@ -11,6 +11,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -49,6 +50,7 @@ impl widget::TkWidget for TkListbox {
 
 impl grid::TkGridLayout for TkListbox {}
 impl pack::TkPackLayout for TkListbox {}
+impl place::TkPlaceLayout for TkListbox {}
 
 impl TkListbox {
     /// Adds item to end of list.
@@ -0,0 +1,386 @@
+//! Log view - a read-only text widget for streams of log lines.
+//!
+//! Lines appended to a log view are collected, and sent to Tk together once
+//! per frame, so logging many lines a second costs little. Only the most
+//! recent lines are kept, up to the view's capacity. The view follows new
+//! lines as they arrive, unless the user has scrolled up to read earlier
+//! ones.
+//!
+//! ```ignore
+//! let log = rish::make_log_view(&root);
+//! log.capacity(5000);
+//! log.append(LogLevel::Info, "started");
+//! log.append(LogLevel::Error, "connection lost");
+//!
+//! // -- only show warnings and errors
+//! log.filter(|level, _| level >= LogLevel::Warn);
+//! ```
+//!
+//! Lines are coloured by level, using tags which can be restyled with
+//! [level_style](TkLogView::level_style). A log view also implements
+//! [std::io::Write], adding each complete line written at level Info.
+//!
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use super::grid;
+use super::pack;
+use super::place;
+use super::text;
+use super::widget;
+use super::wish;
+
+// Time to wait, in milliseconds, after a line is appended, before sending
+// lines to Tk.
+const FRAME: u64 = 16;
+
+/// Severity of a log line, used to choose its colour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
+type LogFilter = Box<dyn Fn(LogLevel, &str) -> bool + Send + 'static>;
+
+struct LogState {
+    capacity: usize,
+    filter: Option<LogFilter>,
+    flush_scheduled: bool,
+    history: VecDeque<(LogLevel, String)>,
+    partial: String,
+    paused: bool,
+    pending: VecDeque<(LogLevel, String)>,
+    shown: usize,
+}
+
+impl LogState {
+    fn new(capacity: usize) -> LogState {
+        LogState {
+            capacity,
+            filter: None,
+            flush_scheduled: false,
+            history: VecDeque::new(),
+            partial: String::new(),
+            paused: false,
+            pending: VecDeque::new(),
+            shown: 0,
+        }
+    }
+
+    // Adds a line to the history and to the lines waiting to be shown,
+    // dropping the oldest lines past the capacity.
+    fn push(&mut self, level: LogLevel, line: &str) {
+        self.history.push_back((level, String::from(line)));
+        self.pending.push_back((level, String::from(line)));
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        if self.capacity > 0 {
+            while self.history.len() > self.capacity {
+                self.history.pop_front();
+            }
+            while self.pending.len() > self.capacity {
+                self.pending.pop_front();
+            }
+        }
+    }
+
+    // Records that `added` lines are inserted in the widget, after clearing
+    // it if `clear`, and returns the number of oldest lines to delete to
+    // keep within the capacity.
+    fn add_shown(&mut self, added: usize, clear: bool) -> usize {
+        if clear {
+            self.shown = 0;
+        }
+        self.shown += added;
+        let excess = if self.capacity > 0 {
+            self.shown.saturating_sub(self.capacity)
+        } else {
+            0
+        };
+        self.shown -= excess;
+        excess
+    }
+
+    // Returns the lines shown by the current filter as a tcl list of
+    // alternating lines and tags, and the number of lines.
+    fn entries<'a>(&self, lines: impl Iterator<Item = &'a (LogLevel, String)>) -> (String, usize) {
+        let mut entries = String::new();
+        let mut count = 0;
+        for (level, line) in lines {
+            if self
+                .filter
+                .as_ref()
+                .is_none_or(|filter| filter(*level, line))
+            {
+                entries.push_str(&format!(
+                    "{} {}{} ",
+                    wish::tcl_quote(line),
+                    TAG_PREFIX,
+                    level
+                ));
+                count += 1;
+            }
+        }
+        (entries, count)
+    }
+}
+
+// Prefix of the tags used for each level.
+const TAG_PREFIX: &str = "rish_log_";
+
+/// Refers to a log view
+#[derive(Clone)]
+pub struct TkLogView {
+    pub id: String,
+    state: Arc<Mutex<LogState>>,
+}
+
+/// Creates an instance of a log view in given parent, keeping up to 10000
+/// lines.
+pub fn make_log_view(parent: &impl widget::TkWidget) -> TkLogView {
+    let id = wish::next_wid(parent.id());
+    let msg = format!(
+        "text {} -state disabled -wrap none ; \
+         {} tag configure {}trace -foreground gray60 ; \
+         {} tag configure {}debug -foreground gray40 ; \
+         {} tag configure {}warn -foreground darkorange3 ; \
+         {} tag configure {}error -foreground red3",
+        id, id, TAG_PREFIX, id, TAG_PREFIX, id, TAG_PREFIX, id, TAG_PREFIX
+    );
+    wish::tell_wish(&msg);
+
+    TkLogView {
+        id,
+        state: Arc::new(Mutex::new(LogState::new(10000))),
+    }
+}
+
+impl widget::TkWidget for TkLogView {
+    /// Returns the widget's id reference - used within tk
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl grid::TkGridLayout for TkLogView {}
+impl pack::TkPackLayout for TkLogView {}
+impl place::TkPlaceLayout for TkLogView {}
+
+impl TkLogView {
+    /// Appends text at the given level: text holding several lines is
+    /// added as separate lines.
+    pub fn append(&self, level: LogLevel, text: &str) {
+        let mut state = self.state.lock().unwrap();
+        for line in text.lines() {
+            state.push(level, line);
+        }
+        self.schedule_flush(&mut state);
+    }
+
+    /// Maximum number of lines kept: older lines are removed as new ones
+    /// are added. 0 for no limit.
+    pub fn capacity(&self, lines: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = lines;
+        state.trim();
+        self.rebuild(&mut state);
+    }
+
+    /// Removes all lines.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.history.clear();
+        state.pending.clear();
+        self.rebuild(&mut state);
+    }
+
+    /// Shows all lines again, after a [filter](TkLogView::filter).
+    pub fn clear_filter(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.filter = None;
+        self.rebuild(&mut state);
+    }
+
+    /// Only shows lines for which the predicate, given the line's level
+    /// and text, returns true. All lines are still kept, so changing or
+    /// clearing the filter shows the lines it hid.
+    pub fn filter(&self, predicate: impl Fn(LogLevel, &str) -> bool + Send + 'static) {
+        let mut state = self.state.lock().unwrap();
+        state.filter = Some(Box::new(predicate));
+        self.rebuild(&mut state);
+    }
+
+    /// Returns true if the view is paused.
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    /// Sets the formatting of lines at the given level.
+    pub fn level_style(&self, level: LogLevel, style: &text::TextTagStyle) {
+        let msg = format!(
+            "{} tag configure {}{} {}",
+            &self.id,
+            TAG_PREFIX,
+            level,
+            style.options_string()
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Stops showing new lines, so the user can read the view undisturbed.
+    /// Lines appended while paused are kept, and shown on
+    /// [resume](TkLogView::resume).
+    pub fn pause(&self) {
+        self.state.lock().unwrap().paused = true;
+    }
+
+    /// Shows the lines appended while paused, and continues showing new
+    /// lines.
+    pub fn resume(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.paused = false;
+        self.schedule_flush(&mut state);
+    }
+
+    // Sends the pending lines to Tk once a frame has passed, unless this
+    // is already arranged.
+    fn schedule_flush(&self, state: &mut LogState) {
+        if !state.flush_scheduled && !state.paused && !state.pending.is_empty() {
+            state.flush_scheduled = true;
+            let log_view = self.clone();
+            widget::after(FRAME, move || log_view.flush());
+        }
+    }
+
+    fn flush(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.flush_scheduled = false;
+        if state.paused {
+            return;
+        }
+        let pending = std::mem::take(&mut state.pending);
+        let (entries, count) = state.entries(pending.iter());
+        let excess = state.add_shown(count, false);
+        let msg = format!(
+            "rish_log_append {} 0 {} [list {}]",
+            &self.id, excess, entries
+        );
+        wish::tell_wish(&msg);
+    }
+
+    // Replaces the lines in the widget with the lines in the history
+    // passing the filter: while paused, the pending lines are left to show
+    // on resume.
+    fn rebuild(&self, state: &mut LogState) {
+        if !state.paused {
+            state.pending.clear();
+        }
+        let shown = state.history.len() - state.pending.len();
+        let (entries, count) = state.entries(state.history.iter().take(shown));
+        let excess = state.add_shown(count, true);
+        let msg = format!(
+            "rish_log_append {} 1 {} [list {}]",
+            &self.id, excess, entries
+        );
+        wish::tell_wish(&msg);
+    }
+}
+
+impl io::Write for TkLogView {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut state = self.state.lock().unwrap();
+        state.partial.push_str(&String::from_utf8_lossy(buf));
+        for line in take_lines(&mut state.partial) {
+            state.push(LogLevel::Info, &line);
+        }
+        self.schedule_flush(&mut state);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let line = std::mem::take(&mut state.partial);
+        if !line.is_empty() {
+            state.push(LogLevel::Info, &line);
+        }
+        self.schedule_flush(&mut state);
+
+        Ok(())
+    }
+}
+
+// Removes and returns the complete lines at the start of text, leaving any
+// incomplete last line.
+fn take_lines(text: &mut String) -> Vec<String> {
+    match text.rfind('\n') {
+        Some(end) => {
+            let rest = text.split_off(end + 1);
+            let lines = text.lines().map(String::from).collect();
+            *text = rest;
+            lines
+        }
+        None => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_take_lines() {
+        let mut text = String::from("one\r\ntwo\nthr");
+        assert_eq!(vec!["one", "two"], take_lines(&mut text));
+        assert_eq!("thr", text);
+        assert!(take_lines(&mut text).is_empty());
+    }
+
+    #[test]
+    fn log_capacity() {
+        let mut state = LogState::new(2);
+        state.filter = Some(Box::new(|level, _| level >= LogLevel::Warn));
+        state.push(LogLevel::Error, "first");
+        state.push(LogLevel::Info, "second");
+        state.push(LogLevel::Warn, "third line");
+        assert_eq!(2, state.history.len());
+        assert_eq!(
+            ("third\\ line rish_log_warn ".to_string(), 1),
+            state.entries(state.history.iter())
+        );
+    }
+
+    #[test]
+    fn log_shown_capacity() {
+        let mut state = LogState::new(3);
+        assert_eq!(0, state.add_shown(2, false));
+        assert_eq!(0, state.add_shown(1, false));
+        assert_eq!(3, state.shown);
+        assert_eq!(2, state.add_shown(2, false));
+        assert_eq!(3, state.shown);
+        assert_eq!(2, state.add_shown(5, true));
+        assert_eq!(3, state.shown);
+    }
+}
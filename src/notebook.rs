@@ -3,17 +3,130 @@
 //!
 //! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/ttk_notebook.htm)
 //!
+//! # Tabs
+//!
+//! Each pane has a tab, whose appearance is set using a [NotebookTab]:
+//!
+//! ```ignore
+//! notebook.add_tab(
+//!     &frame,
+//!     NotebookTab::default()
+//!         .text("Settings")
+//!         .image(&icon)
+//!         .compound(rish::Compound::Left),
+//! );
+//! ```
+//!
+//! Tabs are referred to by their index position, from 0: use
+//! [index](TkNotebook::index) to find the tab holding a given widget.
+//!
 //! # Events
 //!
-//! Use [bind](widget::TkWidget::bind) to call a function on following event:
+//! Use [bind](widget::TkWidget::bind) to call a function on following event,
+//! or [on_tab_changed](TkNotebook::on_tab_changed) to be passed the index of
+//! the selected tab:
 //!
 //! * `<<NotebookTabChanged>>` - when new tab selected
 
+use std::fmt;
+
 use super::grid;
+use super::image;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
+/// State of a notebook tab.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TabState {
+    /// the tab cannot be selected
+    Disabled,
+    /// the tab is not shown
+    Hidden,
+    Normal,
+}
+
+impl fmt::Display for TabState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            TabState::Disabled => "disabled",
+            TabState::Hidden => "hidden",
+            TabState::Normal => "normal",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
+/// Refers to the options of a notebook tab.
+///
+/// The methods on this struct set the values of different options in a
+/// builder style: pass the result to [add_tab](TkNotebook::add_tab),
+/// [insert](TkNotebook::insert) or [tab](TkNotebook::tab). Options not set
+/// are left unchanged.
+///
+#[derive(Clone, Debug, Default)]
+pub struct NotebookTab {
+    options: widget::OptionList,
+}
+
+impl NotebookTab {
+    /// Position of the image relative to the text.
+    pub fn compound(&mut self, value: widget::Compound) -> &mut Self {
+        self.set("compound", &value.to_string())
+    }
+
+    /// Image to show in the tab.
+    pub fn image(&mut self, image: &image::TkImage) -> &mut Self {
+        self.set("image", &image.id)
+    }
+
+    /// Sets space around the pane. Takes
+    /// an array of up to four values, specifying:
+    ///
+    /// * \[all]
+    /// * [left-right top-bottom]
+    /// * [left top-bottom right]
+    /// * [left top right bottom]
+    pub fn padding(&mut self, values: &[u64]) -> &mut Self {
+        let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+        self.set("padding", &values.join(" "))
+    }
+
+    /// Whether the tab is shown, and can be selected.
+    pub fn state(&mut self, value: TabState) -> &mut Self {
+        self.set("state", &value.to_string())
+    }
+
+    /// How the pane is placed within the notebook, if smaller.
+    pub fn sticky(&mut self, value: widget::Sticky) -> &mut Self {
+        self.set("sticky", &value.to_string())
+    }
+
+    /// Text to show in the tab.
+    pub fn text(&mut self, value: &str) -> &mut Self {
+        self.set("text", value)
+    }
+
+    /// Underlines the character at the given index position: used with
+    /// [enable_traversal](TkNotebook::enable_traversal) to select the tab
+    /// with Alt and that character.
+    pub fn underline(&mut self, index: u64) -> &mut Self {
+        self.set("underline", &index.to_string())
+    }
+
+    // Sets option to value, replacing any value already set.
+    fn set(&mut self, option: &'static str, value: &str) -> &mut Self {
+        self.options.set(option, value);
+        self
+    }
+
+    // Returns the options set, in form "-option value ...".
+    fn options_string(&self) -> String {
+        self.options.to_string()
+    }
+}
+
 /// Refers to a notebook widget
 #[derive(Clone, Debug, PartialEq)]
 pub struct TkNotebook {
@@ -38,19 +151,91 @@ impl widget::TkWidget for TkNotebook {
 
 impl grid::TkGridLayout for TkNotebook {}
 impl pack::TkPackLayout for TkNotebook {}
+impl place::TkPlaceLayout for TkNotebook {}
 
 impl TkNotebook {
-    /// TODO: use builder pattern to support images+text
+    /// Adds a pane at the end of the notebook, with the given title on its
+    /// tab: use [add_tab](TkNotebook::add_tab) to set other options.
     pub fn add(&self, widget: &impl widget::TkWidget, title: &str) {
         let msg = format!("{} add {} -text {{{}}}", self.id, widget.id(), title);
         wish::tell_wish(&msg);
     }
 
+    /// Adds a pane at the end of the notebook, with the given tab options,
+    /// or shows a [hidden](TkNotebook::hide) pane again.
+    pub fn add_tab(&self, widget: &impl widget::TkWidget, tab: &NotebookTab) {
+        let msg = format!("{} add {} {}", self.id, widget.id(), tab.options_string());
+        wish::tell_wish(&msg);
+    }
+
+    /// Allows the user to move between tabs with the keyboard:
+    /// Control-Tab and Control-Shift-Tab, and Alt with a tab's
+    /// [underlined](NotebookTab::underline) character.
+    pub fn enable_traversal(&self) {
+        let msg = format!("ttk::notebook::enableTraversal {}", self.id);
+        wish::tell_wish(&msg);
+    }
+
+    /// Removes the tab at the given index, and its pane, from the notebook.
+    /// The pane's widget is not destroyed.
+    pub fn forget(&self, index: u64) {
+        let msg = format!("{} forget {}", self.id, index);
+        wish::tell_wish(&msg);
+    }
+
     /// Height of notebook, in rows
     pub fn height(&self, height: u64) {
         widget::configure(&self.id, "height", &height.to_string());
     }
 
+    /// Hides the tab at the given index: it can be shown again using
+    /// [add_tab](TkNotebook::add_tab) with the same widget.
+    pub fn hide(&self, index: u64) {
+        let msg = format!("{} hide {}", self.id, index);
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns the index of the tab holding the given widget.
+    pub fn index(&self, widget: &impl widget::TkWidget) -> Option<u64> {
+        let msg = format!(
+            "puts [catch {{{} index {}}} result ; set result] ; flush stdout",
+            self.id,
+            widget.id()
+        );
+        let result = wish::ask_wish(&msg);
+        result.parse::<u64>().ok()
+    }
+
+    /// Inserts a pane, with the given tab options, before the tab at the
+    /// given index position. An existing pane is moved to that position.
+    pub fn insert(&self, index: u64, widget: &impl widget::TkWidget, tab: &NotebookTab) {
+        let msg = format!(
+            "{} insert {} {} {}",
+            self.id,
+            index,
+            widget.id(),
+            tab.options_string()
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Sets the function to be called when a different tab is selected,
+    /// which is passed the index of the selected tab. Other bindings of
+    /// `<<NotebookTabChanged>>` are kept.
+    pub fn on_tab_changed(&self, command: impl Fn(u64) + Send + 'static) {
+        let notebook = self.clone();
+        let key = wish::callback_key(&self.id, "tabchanged");
+        wish::add_callback0(
+            &key,
+            wish::mk_callback0(move || {
+                if let Some(index) = notebook.selected() {
+                    command(index);
+                }
+            }),
+        );
+        widget::bind_callback0(&self.id, "<<NotebookTabChanged>>", &key);
+    }
+
     /// Sets space around the widget. Takes
     /// an array of up to four values, specifying:
     ///
@@ -62,8 +247,76 @@ impl TkNotebook {
         widget::padding(&self.id, values);
     }
 
+    /// Selects the tab at the given index, showing its pane.
+    pub fn select(&self, index: u64) {
+        let msg = format!("{} select {}", self.id, index);
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns the index of the selected tab, or None if there are no tabs.
+    pub fn selected(&self) -> Option<u64> {
+        let msg = format!(
+            "puts [expr {{[{0} select] eq {{}} ? {{}} : [{0} index current]}}] ; flush stdout",
+            self.id
+        );
+        let result = wish::ask_wish(&msg);
+        result.parse::<u64>().ok()
+    }
+
+    /// Returns the id of the widget in the selected tab's pane, or None if
+    /// there are no tabs.
+    pub fn selected_widget(&self) -> Option<String> {
+        let msg = format!("puts [{} select] ; flush stdout", self.id);
+        let result = wish::ask_wish(&msg);
+
+        if result.is_empty() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+
+    /// Sets options of the tab at the given index.
+    pub fn tab(&self, index: u64, tab: &NotebookTab) {
+        let msg = format!("{} tab {} {}", self.id, index, tab.options_string());
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns the number of tabs, including hidden tabs.
+    pub fn tab_count(&self) -> u64 {
+        let msg = format!("puts [{} index end] ; flush stdout", self.id);
+        let result = wish::ask_wish(&msg);
+        result.parse::<u64>().unwrap_or(0)
+    }
+
+    /// Returns the ids of the widgets in each pane, in tab order.
+    pub fn tabs(&self) -> Vec<String> {
+        let msg = format!("puts [{} tabs] ; flush stdout", self.id);
+        let result = wish::ask_wish(&msg);
+        wish::split_items(&result)
+    }
+
     /// Width of notebook, in columns
     pub fn width(&self, width: u64) {
         widget::configure(&self.id, "width", &width.to_string());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notebook_tab_options() {
+        assert_eq!("", NotebookTab::default().options_string());
+        assert_eq!(
+            "-text Open\\ files -underline 0 -state hidden",
+            NotebookTab::default()
+                .state(TabState::Disabled)
+                .text("Open files")
+                .underline(0)
+                .state(TabState::Hidden)
+                .options_string()
+        );
+    }
+}
@@ -5,6 +5,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -35,6 +36,7 @@ impl widget::TkWidget for TkPanedWindow {
 }
 impl grid::TkGridLayout for TkPanedWindow {}
 impl pack::TkPackLayout for TkPanedWindow {}
+impl place::TkPlaceLayout for TkPanedWindow {}
 
 impl TkPanedWindow {
     /// Adds given widget to the pane.
@@ -78,6 +80,27 @@ impl TkPanedWindow {
         wish::tell_wish(&msg);
     }
 
+    /// Returns the ids of the widgets in each pane, in order.
+    pub fn panes(&self) -> Vec<String> {
+        let msg = format!("puts [{} panes] ; flush stdout", self.id);
+        let result = wish::ask_wish(&msg);
+        wish::split_list(&result)
+    }
+
+    /// Moves the sash at given index, between panes index and index+1, to
+    /// given position in pixels.
+    pub fn sash_position(&self, index: u64, position: u64) {
+        let msg = format!("{} sashpos {} {}", self.id, index, position);
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns the position in pixels of the sash at given index.
+    pub fn sash_position_get(&self, index: u64) -> u64 {
+        let msg = format!("puts [{} sashpos {}] ; flush stdout", self.id, index);
+        let result = wish::ask_wish(&msg);
+        result.parse::<u64>().unwrap_or(0)
+    }
+
     /// Width of paned window, in columns
     pub fn width(&self, width: u64) {
         widget::configure(&self.id, "width", &width.to_string());
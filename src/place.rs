@@ -0,0 +1,328 @@
+//! Place layout - a geometry manager for positioning widgets exactly.
+//!
+//! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/place.htm)
+//!
+//! The place-layout is used in a "builder" style to layout a single widget:
+//!
+//! ```ignore
+//! widget.place()
+//!   .OPTION(VALUE) // 0 or more
+//!   .layout();
+//! ```
+//!
+//! 1. `place` is called first, to get the PlaceLayout instance.
+//! 2. `layout` must be called last, to perform the layout.
+//! 3. zero or more options are added to the PlaceLayout, to control the position
+//!    and size of the widget.
+//!
+//! Positions and sizes are given in pixels, or relative to the container,
+//! where 0.0 is its left/top edge and 1.0 its right/bottom edge: when both
+//! are given, they are added together.
+//!
+//! ## Example
+//!
+//! The following example places a badge over the top-right corner of a
+//! button:
+//!
+//! ```ignore
+//! badge.place()                   // starts layout definition
+//!     .inside(&button)            // relative to the button
+//!     .relx(1.0)                  // at its right-hand edge
+//!     .x(-4)                      // moved 4 pixels in
+//!     .y(4)                       // and 4 pixels down
+//!     .anchor(rish::Anchor::NE)   // by the badge's top-right corner
+//!     .layout();                  // completes the layout
+//! ```
+//!
+
+use super::widget;
+use super::wish;
+
+/// Refers to the settings for a PlaceLayout.
+///
+/// Apart from `layout`, the methods on this struct set the values of different
+/// options in a builder style: call `layout` to finish the layout.
+///
+/// The settings of a widget already laid out are returned by
+/// [place_info](TkPlaceLayout::place_info), and can be read with the `_get`
+/// methods.
+///
+#[derive(Clone, Debug)]
+pub struct PlaceLayout {
+    id: String,
+    anchor: Option<widget::Anchor>,
+    border_mode: Option<widget::BorderMode>,
+    height: Option<u64>,
+    inside: Option<String>,
+    relheight: Option<f64>,
+    relwidth: Option<f64>,
+    relx: Option<f64>,
+    rely: Option<f64>,
+    width: Option<u64>,
+    x: Option<i64>,
+    y: Option<i64>,
+}
+
+impl PlaceLayout {
+    pub(super) fn new(wid: &str) -> PlaceLayout {
+        PlaceLayout {
+            id: String::from(wid),
+            anchor: None,
+            border_mode: None,
+            height: None,
+            inside: None,
+            relheight: None,
+            relwidth: None,
+            relx: None,
+            rely: None,
+            width: None,
+            x: None,
+            y: None,
+        }
+    }
+
+    // Creates the settings described by the option-value list returned by
+    // "place info".
+    pub(super) fn from_info(wid: &str, info: &str) -> PlaceLayout {
+        let mut layout = PlaceLayout::new(wid);
+        let parts = wish::split_list(info);
+
+        for pair in parts.chunks_exact(2) {
+            let value = pair[1].as_str();
+            if value.is_empty() {
+                continue;
+            }
+            match pair[0].as_str() {
+                "-anchor" => layout.anchor = Some(widget::Anchor::from_name(value)),
+                "-bordermode" => layout.border_mode = Some(widget::BorderMode::from_name(value)),
+                "-height" => layout.height = value.parse::<u64>().ok(),
+                "-in" => layout.inside = Some(String::from(value)),
+                "-relheight" => layout.relheight = value.parse::<f64>().ok(),
+                "-relwidth" => layout.relwidth = value.parse::<f64>().ok(),
+                "-relx" => layout.relx = value.parse::<f64>().ok(),
+                "-rely" => layout.rely = value.parse::<f64>().ok(),
+                "-width" => layout.width = value.parse::<u64>().ok(),
+                "-x" => layout.x = value.parse::<i64>().ok(),
+                "-y" => layout.y = value.parse::<i64>().ok(),
+                _ => {}
+            }
+        }
+
+        layout
+    }
+
+    /// Which point of this widget is placed at the given position.
+    pub fn anchor(&mut self, value: widget::Anchor) -> &mut Self {
+        self.anchor = Some(value);
+        self
+    }
+
+    /// Returns which point of this widget is placed at its position, if set.
+    pub fn anchor_get(&self) -> Option<widget::Anchor> {
+        self.anchor.clone()
+    }
+
+    /// Whether the container's border is included in its area.
+    pub fn border_mode(&mut self, value: widget::BorderMode) -> &mut Self {
+        self.border_mode = Some(value);
+        self
+    }
+
+    /// Returns whether the container's border is included in its area, if set.
+    pub fn border_mode_get(&self) -> Option<widget::BorderMode> {
+        self.border_mode.clone()
+    }
+
+    /// Height of this widget, in pixels.
+    pub fn height(&mut self, height: u64) -> &mut Self {
+        self.height = Some(height);
+        self
+    }
+
+    /// Returns the height of this widget, in pixels, if set.
+    pub fn height_get(&self) -> Option<u64> {
+        self.height
+    }
+
+    /// Places this widget relative to given widget, rather than its parent:
+    /// the widget must be a descendant of this widget's parent.
+    pub fn inside(&mut self, widget: &impl widget::TkWidget) -> &mut Self {
+        self.inside = Some(String::from(widget.id()));
+        self
+    }
+
+    /// Returns the id of the widget this widget is placed relative to, if set.
+    pub fn inside_get(&self) -> Option<&str> {
+        self.inside.as_deref()
+    }
+
+    /// Height of this widget, relative to the container's height.
+    pub fn relheight(&mut self, height: f64) -> &mut Self {
+        self.relheight = Some(height);
+        self
+    }
+
+    /// Returns the height of this widget, relative to the container, if set.
+    pub fn relheight_get(&self) -> Option<f64> {
+        self.relheight
+    }
+
+    /// Width of this widget, relative to the container's width.
+    pub fn relwidth(&mut self, width: f64) -> &mut Self {
+        self.relwidth = Some(width);
+        self
+    }
+
+    /// Returns the width of this widget, relative to the container, if set.
+    pub fn relwidth_get(&self) -> Option<f64> {
+        self.relwidth
+    }
+
+    /// Horizontal position, relative to the container's width.
+    pub fn relx(&mut self, x: f64) -> &mut Self {
+        self.relx = Some(x);
+        self
+    }
+
+    /// Returns the horizontal position, relative to the container, if set.
+    pub fn relx_get(&self) -> Option<f64> {
+        self.relx
+    }
+
+    /// Vertical position, relative to the container's height.
+    pub fn rely(&mut self, y: f64) -> &mut Self {
+        self.rely = Some(y);
+        self
+    }
+
+    /// Returns the vertical position, relative to the container, if set.
+    pub fn rely_get(&self) -> Option<f64> {
+        self.rely
+    }
+
+    /// Width of this widget, in pixels.
+    pub fn width(&mut self, width: u64) -> &mut Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Returns the width of this widget, in pixels, if set.
+    pub fn width_get(&self) -> Option<u64> {
+        self.width
+    }
+
+    /// Horizontal position, in pixels.
+    pub fn x(&mut self, x: i64) -> &mut Self {
+        self.x = Some(x);
+        self
+    }
+
+    /// Returns the horizontal position, in pixels, if set.
+    pub fn x_get(&self) -> Option<i64> {
+        self.x
+    }
+
+    /// Vertical position, in pixels.
+    pub fn y(&mut self, y: i64) -> &mut Self {
+        self.y = Some(y);
+        self
+    }
+
+    /// Returns the vertical position, in pixels, if set.
+    pub fn y_get(&self) -> Option<i64> {
+        self.y
+    }
+
+    /// Called last to finally create the layout with the parameter values
+    /// set up by the builder.
+    pub fn layout(&self) {
+        let mut msg = format!("place {} ", self.id);
+        if let Some(anchor) = &self.anchor {
+            msg.push_str(&format!("-anchor {} ", anchor));
+        }
+        if let Some(border_mode) = &self.border_mode {
+            msg.push_str(&format!("-bordermode {} ", border_mode));
+        }
+        if let Some(height) = self.height {
+            msg.push_str(&format!("-height {} ", height));
+        }
+        if let Some(inside) = &self.inside {
+            msg.push_str(&format!("-in {} ", inside));
+        }
+        if let Some(height) = self.relheight {
+            msg.push_str(&format!("-relheight {} ", height));
+        }
+        if let Some(width) = self.relwidth {
+            msg.push_str(&format!("-relwidth {} ", width));
+        }
+        if let Some(x) = self.relx {
+            msg.push_str(&format!("-relx {} ", x));
+        }
+        if let Some(y) = self.rely {
+            msg.push_str(&format!("-rely {} ", y));
+        }
+        if let Some(width) = self.width {
+            msg.push_str(&format!("-width {} ", width));
+        }
+        if let Some(x) = self.x {
+            msg.push_str(&format!("-x {} ", x));
+        }
+        if let Some(y) = self.y {
+            msg.push_str(&format!("-y {} ", y));
+        }
+
+        wish::tell_wish(&msg);
+    }
+}
+
+/// Common functions for widgets that can be arranged using PlaceLayouts
+pub trait TkPlaceLayout: widget::TkWidget {
+    /// Creates a PlaceLayout instance for placing this widget within its
+    /// parent.
+    fn place(&self) -> PlaceLayout {
+        PlaceLayout::new(self.id())
+    }
+
+    /// Sets properties for widget layout directly through Tk: see Tk
+    /// [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/place.htm) for
+    /// options.
+    fn place_configure(&self, option: &str, value: &str) {
+        let msg = format!("place configure {} -{} {{{}}}", self.id(), option, value);
+        wish::tell_wish(&msg);
+    }
+
+    /// Removes this widget from layout.
+    fn place_forget(&self) {
+        let msg = format!("place forget {}", self.id());
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns the current layout settings of this widget.
+    fn place_info(&self) -> PlaceLayout {
+        let msg = format!("puts [place info {}] ; flush stdout", self.id());
+        let result = wish::ask_wish(&msg);
+        PlaceLayout::from_info(self.id(), &result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn place_info_parse() {
+        let layout = PlaceLayout::from_info(
+            ".r2",
+            "-in .r1 -x -4 -relx 1 -y 4 -rely 0 -width {} -relwidth 0.5 -height 20 -relheight {} -anchor ne -bordermode inside",
+        );
+        assert_eq!(Some(".r1"), layout.inside_get());
+        assert_eq!(Some(-4), layout.x_get());
+        assert_eq!(Some(1.0), layout.relx_get());
+        assert_eq!(None, layout.width_get());
+        assert_eq!(Some(0.5), layout.relwidth_get());
+        assert_eq!(Some(20), layout.height_get());
+        assert_eq!(None, layout.relheight_get());
+        assert_eq!(Some(widget::Anchor::NE), layout.anchor_get());
+        assert_eq!(Some(widget::BorderMode::Inside), layout.border_mode_get());
+    }
+}
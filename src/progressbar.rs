@@ -5,6 +5,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -40,6 +41,7 @@ impl widget::TkWidget for TkProgressbar {
 
 impl grid::TkGridLayout for TkProgressbar {}
 impl pack::TkPackLayout for TkProgressbar {}
+impl place::TkPlaceLayout for TkProgressbar {}
 
 impl TkProgressbar {
     /// Displayed length of progress bar in pixels.
@@ -6,6 +6,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -42,6 +43,7 @@ impl widget::TkWidget for TkRadioButton {
 }
 impl grid::TkGridLayout for TkRadioButton {}
 impl pack::TkPackLayout for TkRadioButton {}
+impl place::TkPlaceLayout for TkRadioButton {}
 impl widget::TkLabelOptions for TkRadioButton {}
 
 impl TkRadioButton {
@@ -5,6 +5,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -33,6 +34,7 @@ impl widget::TkWidget for TkScale {
 
 impl grid::TkGridLayout for TkScale {}
 impl pack::TkPackLayout for TkScale {}
+impl place::TkPlaceLayout for TkScale {}
 
 impl TkScale {
     /// Sets the function to be called whenever the scale value is changed.
@@ -5,6 +5,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -63,5 +64,6 @@ impl widget::TkWidget for TkScrollbar {
 
 impl grid::TkGridLayout for TkScrollbar {}
 impl pack::TkPackLayout for TkScrollbar {}
+impl place::TkPlaceLayout for TkScrollbar {}
 
 impl TkScrollbar {}
@@ -7,6 +7,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -37,3 +38,4 @@ impl widget::TkWidget for TkSeparator {
 
 impl grid::TkGridLayout for TkSeparator {}
 impl pack::TkPackLayout for TkSeparator {}
+impl place::TkPlaceLayout for TkSeparator {}
@@ -0,0 +1,441 @@
+//! Session - saves the state of windows and panes, to restore when the
+//! program is next run.
+//!
+//! Widgets are registered with a [Session] under names, which identify
+//! them from one run to the next. A [snapshot](Session::snapshot) records
+//! the state of each widget in a [SessionState]:
+//!
+//! * the size and position of a top-level window,
+//! * the sash positions of a paned window,
+//! * the selected tab of a notebook, and
+//! * the column widths, and open items, of a treeview.
+//!
+//! A session state is written as text, one widget per line, using
+//! `to_string`, and read back with `parse`:
+//!
+//! ```ignore
+//! let mut session = rish::Session::default();
+//! session
+//!     .top_level("main", &root)
+//!     .paned_window("split", &panes)
+//!     .notebook("tabs", &notebook)
+//!     .treeview("files", &files);
+//!
+//! // -- at startup, once the widgets are created
+//! let _ = session.load("app.session");
+//!
+//! // -- before exiting
+//! session.save("app.session").unwrap();
+//! ```
+//!
+//! When restored, a window's geometry is fitted to the current screen, so a
+//! window saved on a larger screen is still visible. State saved for
+//! widgets no longer registered, or no longer present, such as a tab or
+//! column since removed, is ignored. Treeview items are saved by id, so
+//! their open state is only restored when the tree is built with the same
+//! ids, or in the same order.
+//!
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str;
+
+use super::notebook;
+use super::paned_window;
+use super::toplevel;
+use super::treeview;
+use super::widget::TkWidget;
+use super::wish;
+
+/// Saved state of a single widget.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SavedState {
+    /// size and position of a top-level window
+    Geometry(toplevel::WindowGeometry),
+    /// positions of a paned window's sashes, in pixels
+    Sashes(Vec<u64>),
+    /// index of a notebook's selected tab
+    SelectedTab(u64),
+    /// width of each of a treeview's columns, including "#0", and the ids
+    /// of its open items
+    Treeview {
+        column_widths: Vec<(String, u64)>,
+        open_items: Vec<String>,
+    },
+}
+
+/// Saved state of the widgets in a session, by name.
+///
+/// Displays as text, one widget per line, which can be parsed back into a
+/// session state.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionState {
+    pub widgets: BTreeMap<String, SavedState>,
+}
+
+impl fmt::Display for SessionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, state) in &self.widgets {
+            write!(f, "{}", wish::tcl_quote(name))?;
+            match state {
+                SavedState::Geometry(geometry) => write!(f, " geometry {}", geometry)?,
+                SavedState::Sashes(positions) => {
+                    write!(f, " sashes")?;
+                    for position in positions {
+                        write!(f, " {}", position)?;
+                    }
+                }
+                SavedState::SelectedTab(index) => write!(f, " tab {}", index)?,
+                SavedState::Treeview {
+                    column_widths,
+                    open_items,
+                } => {
+                    let columns: Vec<String> = column_widths
+                        .iter()
+                        .map(|(column, width)| format!("{} {}", wish::tcl_quote(column), width))
+                        .collect();
+                    let items: Vec<String> =
+                        open_items.iter().map(|id| wish::tcl_quote(id)).collect();
+                    write!(
+                        f,
+                        " treeview {} {}",
+                        wish::tcl_quote(&columns.join(" ")),
+                        wish::tcl_quote(&items.join(" "))
+                    )?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for SessionState {
+    type Err = wish::TkError;
+
+    /// Parse from the text written by `to_string`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut session = SessionState::default();
+
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let invalid = || wish::TkError::new(&format!("Invalid session line: {}", line));
+            let parts = wish::split_list(line);
+            let (name, kind, values) = match parts.as_slice() {
+                [name, kind, values @ ..] => (name, kind.as_str(), values),
+                _ => return Err(invalid()),
+            };
+            let state = match (kind, values) {
+                ("geometry", [geometry]) => {
+                    SavedState::Geometry(geometry.parse().map_err(|_| invalid())?)
+                }
+                ("sashes", values) => SavedState::Sashes(
+                    values
+                        .iter()
+                        .map(|value| value.parse::<u64>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| invalid())?,
+                ),
+                ("tab", [index]) => {
+                    SavedState::SelectedTab(index.parse::<u64>().map_err(|_| invalid())?)
+                }
+                ("treeview", [columns, items]) => SavedState::Treeview {
+                    column_widths: wish::split_list(columns)
+                        .chunks(2)
+                        .map(|pair| match pair {
+                            [column, width] => Some((column.clone(), width.parse::<u64>().ok()?)),
+                            _ => None,
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(invalid)?,
+                    open_items: wish::split_list(items),
+                },
+                _ => return Err(invalid()),
+            };
+            session.widgets.insert(name.clone(), state);
+        }
+
+        Ok(session)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Tracked {
+    Notebook(notebook::TkNotebook),
+    PanedWindow(paned_window::TkPanedWindow),
+    TopLevel(toplevel::TkTopLevel),
+    Treeview(treeview::TkTreeview),
+}
+
+/// Widgets whose state is saved and restored, by name.
+///
+/// Widgets are registered in a "builder" style, and should be registered
+/// under the same names on each run.
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    widgets: Vec<(String, Tracked)>,
+}
+
+impl Session {
+    /// Reads the session state saved in the file at path, and restores it.
+    pub fn load(&self, path: impl AsRef<Path>) -> Result<(), wish::TkError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|error| {
+            wish::TkError::new(&format!("Cannot read {}: {}", path.display(), error))
+        })?;
+        self.restore(&text.parse::<SessionState>()?);
+        Ok(())
+    }
+
+    /// Registers a notebook, to save its selected tab.
+    pub fn notebook(&mut self, name: &str, notebook: &notebook::TkNotebook) -> &mut Self {
+        self.add(name, Tracked::Notebook(notebook.clone()))
+    }
+
+    /// Registers a paned window, to save its sash positions.
+    pub fn paned_window(
+        &mut self,
+        name: &str,
+        paned_window: &paned_window::TkPanedWindow,
+    ) -> &mut Self {
+        self.add(name, Tracked::PanedWindow(paned_window.clone()))
+    }
+
+    /// Restores the registered widgets to their state in the given session
+    /// state.
+    pub fn restore(&self, state: &SessionState) {
+        // -- panes are only sized once their window has been laid out
+        wish::tell_wish("update idletasks");
+
+        for (name, widget) in &self.widgets {
+            match (widget, state.widgets.get(name)) {
+                (Tracked::Notebook(notebook), Some(SavedState::SelectedTab(index)))
+                    if *index < notebook.tab_count() =>
+                {
+                    notebook.select(*index);
+                }
+                (Tracked::PanedWindow(paned_window), Some(SavedState::Sashes(positions))) => {
+                    let sashes = paned_window.panes().len().saturating_sub(1);
+                    for (index, position) in positions.iter().take(sashes).enumerate() {
+                        paned_window.sash_position(index as u64, *position);
+                    }
+                }
+                (Tracked::TopLevel(top_level), Some(SavedState::Geometry(geometry))) => {
+                    let screen = (top_level.screen_width(), top_level.screen_height());
+                    if let Some(geometry) = fit_geometry(geometry, screen) {
+                        top_level.set_geometry(&geometry);
+                    }
+                }
+                (
+                    Tracked::Treeview(treeview),
+                    Some(SavedState::Treeview {
+                        column_widths,
+                        open_items,
+                    }),
+                ) => {
+                    let current = treeview.column_widths();
+                    for (column, width) in column_widths {
+                        if current.iter().any(|(name, _)| name == column) {
+                            treeview.column_width(column, *width);
+                        }
+                    }
+                    for id in open_items {
+                        let item = treeview.item(id);
+                        if item.exists() {
+                            item.open(true);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Saves the state of the registered widgets to the file at path.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), wish::TkError> {
+        let path = path.as_ref();
+        fs::write(path, self.snapshot().to_string()).map_err(|error| {
+            wish::TkError::new(&format!("Cannot write {}: {}", path.display(), error))
+        })
+    }
+
+    /// Returns the current state of the registered widgets.
+    pub fn snapshot(&self) -> SessionState {
+        let mut state = SessionState::default();
+
+        for (name, widget) in &self.widgets {
+            let saved = match widget {
+                Tracked::Notebook(notebook) => notebook.selected().map(SavedState::SelectedTab),
+                Tracked::PanedWindow(paned_window) => {
+                    let sashes = paned_window.panes().len().saturating_sub(1) as u64;
+                    Some(SavedState::Sashes(
+                        (0..sashes)
+                            .map(|index| paned_window.sash_position_get(index))
+                            .collect(),
+                    ))
+                }
+                Tracked::TopLevel(top_level) => {
+                    top_level.geometry_get().ok().map(SavedState::Geometry)
+                }
+                Tracked::Treeview(treeview) => Some(SavedState::Treeview {
+                    column_widths: treeview.column_widths(),
+                    open_items: treeview
+                        .open_items()
+                        .iter()
+                        .map(|item| item.id.clone())
+                        .collect(),
+                }),
+            };
+            if let Some(saved) = saved {
+                state.widgets.insert(name.clone(), saved);
+            }
+        }
+
+        state
+    }
+
+    /// Registers a top-level window, to save its size and position.
+    pub fn top_level(&mut self, name: &str, top_level: &toplevel::TkTopLevel) -> &mut Self {
+        self.add(name, Tracked::TopLevel(top_level.clone()))
+    }
+
+    /// Registers a treeview, to save its column widths and open items.
+    pub fn treeview(&mut self, name: &str, treeview: &treeview::TkTreeview) -> &mut Self {
+        self.add(name, Tracked::Treeview(treeview.clone()))
+    }
+
+    fn add(&mut self, name: &str, widget: Tracked) -> &mut Self {
+        self.widgets.retain(|(other, _)| other != name);
+        self.widgets.push((String::from(name), widget));
+        self
+    }
+}
+
+// Fits the geometry of a window onto a screen of size (width, height),
+// shrinking and moving the window as needed, while keeping the screen edges
+// its position is measured from: returns None if the geometry is empty.
+fn fit_geometry(
+    geometry: &toplevel::WindowGeometry,
+    screen: (u64, u64),
+) -> Option<toplevel::WindowGeometry> {
+    if geometry.width == 0 || geometry.height == 0 || screen.0 == 0 || screen.1 == 0 {
+        return None;
+    }
+    let width = geometry.width.min(screen.0);
+    let height = geometry.height.min(screen.1);
+
+    Some(toplevel::WindowGeometry {
+        width,
+        height,
+        x: fit_offset(geometry.x, screen.0 - width),
+        y: fit_offset(geometry.y, screen.1 - height),
+    })
+}
+
+// Keeps the offset between 0 and the given room, from the same edge.
+fn fit_offset(offset: toplevel::WindowOffset, room: u64) -> toplevel::WindowOffset {
+    let room = room as i64;
+    match offset {
+        toplevel::WindowOffset::FromStart(value) => {
+            toplevel::WindowOffset::FromStart(value.clamp(0, room))
+        }
+        toplevel::WindowOffset::FromEnd(value) => {
+            toplevel::WindowOffset::FromEnd(value.clamp(0, room))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toplevel::{WindowGeometry, WindowOffset};
+
+    #[test]
+    fn session_text_round_trip() {
+        let mut state = SessionState::default();
+        state.widgets.insert(
+            String::from("main window"),
+            SavedState::Geometry(WindowGeometry::new(
+                800,
+                600,
+                WindowOffset::FromStart(-5),
+                WindowOffset::FromEnd(20),
+            )),
+        );
+        state
+            .widgets
+            .insert(String::from("split"), SavedState::Sashes(vec![120, 340]));
+        state
+            .widgets
+            .insert(String::from("tabs"), SavedState::SelectedTab(2));
+        state.widgets.insert(
+            String::from("files"),
+            SavedState::Treeview {
+                column_widths: vec![(String::from("#0"), 200), (String::from("file size"), 80)],
+                open_items: vec![String::from("I001"), String::from("src dir")],
+            },
+        );
+
+        let text = state.to_string();
+        assert_eq!(4, text.lines().count());
+        assert_eq!(state, text.parse::<SessionState>().unwrap());
+    }
+
+    #[test]
+    fn session_text_errors() {
+        assert!("main geometry 800 600 10".parse::<SessionState>().is_err());
+        assert!("main geometry 800x600+10".parse::<SessionState>().is_err());
+        assert!("tabs tab first".parse::<SessionState>().is_err());
+        assert!("files treeview {#0} {}".parse::<SessionState>().is_err());
+        assert!("files treeview {#0 wide} {}"
+            .parse::<SessionState>()
+            .is_err());
+        assert!("files treeview {#0 200}".parse::<SessionState>().is_err());
+        assert!("tabs".parse::<SessionState>().is_err());
+        assert_eq!(
+            SessionState::default(),
+            "\n\n".parse::<SessionState>().unwrap()
+        );
+    }
+
+    #[test]
+    fn session_fit_geometry() {
+        use WindowOffset::*;
+
+        let screen = (1920, 1080);
+        let fit =
+            |width, height, x, y| fit_geometry(&WindowGeometry::new(width, height, x, y), screen);
+        assert_eq!(
+            Some(WindowGeometry::new(800, 600, FromStart(10), FromStart(20))),
+            fit(800, 600, FromStart(10), FromStart(20))
+        );
+        assert_eq!(
+            Some(WindowGeometry::new(800, 600, FromStart(0), FromStart(480))),
+            fit(800, 600, FromStart(-5), FromStart(3000))
+        );
+        assert_eq!(
+            Some(WindowGeometry::new(1920, 1080, FromStart(0), FromStart(0))),
+            fit(2560, 1440, FromStart(100), FromStart(100))
+        );
+        assert_eq!(None, fit(0, 0, FromStart(0), FromStart(0)));
+    }
+
+    #[test]
+    fn session_fit_geometry_from_end() {
+        use WindowOffset::*;
+
+        // -- a window near the bottom-right corner stays there
+        let screen = (1920, 1080);
+        let fit =
+            |width, height, x, y| fit_geometry(&WindowGeometry::new(width, height, x, y), screen);
+        assert_eq!(
+            Some(WindowGeometry::new(800, 600, FromEnd(20), FromEnd(30))),
+            fit(800, 600, FromEnd(20), FromEnd(30))
+        );
+        assert_eq!(
+            Some(WindowGeometry::new(800, 600, FromEnd(0), FromEnd(480))),
+            fit(800, 600, FromEnd(-5), FromEnd(3000))
+        );
+    }
+}
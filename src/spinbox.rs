@@ -6,6 +6,7 @@
 
 use super::grid;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -44,6 +45,7 @@ impl widget::TkWidget for TkSpinboxRange {
 
 impl grid::TkGridLayout for TkSpinboxRange {}
 impl pack::TkPackLayout for TkSpinboxRange {}
+impl place::TkPlaceLayout for TkSpinboxRange {}
 
 impl TkSpinboxRange {
     /// Sets the state of the widget; Readonly means user cannot enter
@@ -103,6 +105,7 @@ impl widget::TkWidget for TkSpinboxValues {
 
 impl grid::TkGridLayout for TkSpinboxValues {}
 impl pack::TkPackLayout for TkSpinboxValues {}
+impl place::TkPlaceLayout for TkSpinboxValues {}
 
 impl TkSpinboxValues {
     /// Sets the state of the widget; Readonly means user cannot enter
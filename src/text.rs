@@ -1,10 +1,32 @@
 //! Text widget - displays text.
 //!
+//! Positions within the text are given as a [TextIndex]: see the [index]
+//! module for the forms an index can take. The [search] module describes
+//! the options for finding text, and the [dump] module how content is saved
+//! and restored. The [highlight] module adds syntax highlighting, and
+//! the [tag] module describes how tagged text is formatted.
+//!
 //! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/text.htm)
 
+pub mod dump;
+pub use dump::{DumpItem, DumpKind};
+
+pub mod highlight;
+
+pub mod index;
+pub use index::*;
+
+pub mod search;
+pub use search::*;
+
+pub mod tag;
+pub use tag::*;
+
+use super::canvas;
 use super::grid;
 use super::image;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -14,6 +36,19 @@ pub struct TkText {
     pub id: String,
 }
 
+/// Position and size of a display line, as returned by
+/// [dline_info](TkText::dline_info): all values are in pixels, relative to
+/// the text widget's window.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayLineInfo {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    /// distance from the top of the line to its baseline
+    pub baseline: i64,
+}
+
 /// Creates an instance of a text widget in given parent.
 pub fn make_text(parent: &impl widget::TkWidget) -> TkText {
     let id = wish::next_wid(parent.id());
@@ -32,8 +67,42 @@ impl widget::TkWidget for TkText {
 
 impl grid::TkGridLayout for TkText {}
 impl pack::TkPackLayout for TkText {}
+impl place::TkPlaceLayout for TkText {}
+
+/// Creates a canvas, in the text widget's parent, showing the line number
+/// of each visible line of text: place the canvas to the left of the text.
+///
+/// The numbers are redrawn whenever the text is scrolled or resized, and
+/// after each edit, including an undo or redo. Scrolling is followed by
+/// wrapping the text's current `yscrollcommand`, so any vertical scrollbar
+/// must be created before the gutter.
+pub fn line_number_gutter(text: &TkText) -> canvas::TkCanvas {
+    let parent = match text.id.rsplit_once('.') {
+        Some(("", _)) | None => ".",
+        Some((parent, _)) => parent,
+    };
+    let id = wish::next_wid(parent);
+    let msg = format!(
+        "canvas {0} -borderwidth 0 -highlightthickness 0 -background [{1} cget -background] ; \
+         {1} configure -yscrollcommand [list rish_gutter_scroll {1} {0} [{1} cget -yscrollcommand]] ; \
+         bind {1} <Configure> {{+rish_gutter_scroll {1} {0} {{}}}} ; \
+         trace add execution {1} leave [list rish_gutter_edit {1} {0}] ; \
+         rish_gutter_scroll {1} {0} {{}}",
+        id, &text.id
+    );
+    wish::tell_wish(&msg);
+
+    canvas::TkCanvas { id }
+}
 
 impl TkText {
+    /// Sets whether separators are added to the undo stack automatically,
+    /// at each insert, delete or change of cursor position: if not, call
+    /// [edit_separator](TkText::edit_separator) to group edits.
+    pub fn auto_separators(&self, value: bool) {
+        widget::configure(&self.id, "autoseparators", if value { "1" } else { "0" });
+    }
+
     /// Specifies the background colour.
     ///
     /// Colours are specified as a string, by either:
@@ -49,22 +118,165 @@ impl TkText {
         widget::configure(&self.id, "borderwidth", &width.to_string());
     }
 
-    /// Delete a range of text.
-    pub fn delete(
-        &self,
-        (from_line, from_character): (u64, u64),
-        (to_line, to_character): (u64, u64),
-    ) {
+    /// Returns the bounding box (x, y, width, height) of the character at
+    /// the given position, if it is visible.
+    pub fn bbox(&self, index: impl Into<TextIndex>) -> Option<(i64, i64, i64, i64)> {
         let msg = format!(
-            "{} delete {}.{} {}.{}",
-            &self.id, from_line, from_character, to_line, to_character
+            "puts [{} bbox {{{}}}] ; flush stdout",
+            &self.id,
+            index.into()
         );
+        let result = wish::ask_wish(&msg);
+        let parts: Vec<i64> = result
+            .split_whitespace()
+            .filter_map(|part| part.parse::<i64>().ok())
+            .collect();
+
+        if parts.len() == 4 {
+            Some((parts[0], parts[1], parts[2], parts[3]))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if there is an edit that can be redone.
+    pub fn can_redo(&self) -> bool {
+        let msg = format!("puts [{} edit canredo] ; flush stdout", &self.id);
+        let result = wish::ask_wish(&msg);
+        result.trim() == "1"
+    }
+
+    /// Returns true if there is an edit that can be undone.
+    pub fn can_undo(&self) -> bool {
+        let msg = format!("puts [{} edit canundo] ; flush stdout", &self.id);
+        let result = wish::ask_wish(&msg);
+        result.trim() == "1"
+    }
+
+    /// Returns the number of display lines between the two positions:
+    /// negative if `to` is before `from`. Wrapped lines count as more than
+    /// one display line, and elided lines are not counted.
+    pub fn count_display_lines(&self, from: impl Into<TextIndex>, to: impl Into<TextIndex>) -> i64 {
+        let msg = format!(
+            "puts [{} count -displaylines {{{}}} {{{}}}] ; flush stdout",
+            &self.id,
+            from.into(),
+            to.into()
+        );
+        let result = wish::ask_wish(&msg);
+        result.trim().parse::<i64>().unwrap_or(0)
+    }
+
+    /// Creates a peer of this text widget in the given parent: a separate
+    /// widget showing the same text, so edits in one appear in the other.
+    /// Marks, scrolling and widget options are independent.
+    pub fn create_peer(&self, parent: &impl widget::TkWidget) -> TkText {
+        let id = wish::next_wid(parent.id());
+        let msg = format!("{} peer create {}", &self.id, id);
+        wish::tell_wish(&msg);
+
+        TkText { id }
+    }
+
+    /// Delete a range of text.
+    pub fn delete(&self, from: impl Into<TextIndex>, to: impl Into<TextIndex>) {
+        let msg = format!("{} delete {{{}}} {{{}}}", &self.id, from.into(), to.into());
         wish::tell_wish(&msg);
     }
 
     /// Delete a single character in text.
-    pub fn delete_char(&self, (line, character): (u64, u64)) {
-        let msg = format!("{} delete {}.{}", &self.id, line, character);
+    pub fn delete_char(&self, index: impl Into<TextIndex>) {
+        let msg = format!("{} delete {{{}}}", &self.id, index.into());
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns the position and size of the display line holding the given
+    /// position, if it is visible.
+    pub fn dline_info(&self, index: impl Into<TextIndex>) -> Option<DisplayLineInfo> {
+        let msg = format!(
+            "puts [{} dlineinfo {{{}}}] ; flush stdout",
+            &self.id,
+            index.into()
+        );
+        let result = wish::ask_wish(&msg);
+        let parts: Vec<i64> = result
+            .split_whitespace()
+            .filter_map(|part| part.parse::<i64>().ok())
+            .collect();
+
+        if let [x, y, width, height, baseline] = parts[..] {
+            Some(DisplayLineInfo {
+                x,
+                y,
+                width,
+                height,
+                baseline,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the content between the given positions as a list of items,
+    /// including only the given kinds of item: all kinds if none are given.
+    ///
+    /// An error is returned if Tk reports one, e.g. for an invalid index.
+    pub fn dump(
+        &self,
+        from: impl Into<TextIndex>,
+        to: impl Into<TextIndex>,
+        kinds: &[DumpKind],
+    ) -> Result<Vec<DumpItem>, wish::TkError> {
+        let mut switches = String::new();
+        if kinds.is_empty() {
+            switches.push_str("-all");
+        }
+        for kind in kinds {
+            switches.push_str(&format!("-{} ", kind));
+        }
+        // -- report the image displayed, not the name of the embedded image
+        let script = format!(
+            "apply {{{{}} {{
+                set result {{}}
+                foreach {{key value index}} [{} dump {} {{{}}} {{{}}}] {{
+                    if {{$key eq {{image}}}} {{
+                        set value [{} image cget $index -image]
+                    }}
+                    lappend result $key $value $index
+                }}
+                return $result
+            }}}}",
+            &self.id,
+            switches,
+            from.into(),
+            to.into(),
+            &self.id
+        );
+        wish::eval_wish(&script).map(|result| dump::parse_dump(&result))
+    }
+
+    /// Redoes the last undone edit, if there is one.
+    pub fn edit_redo(&self) {
+        let msg = format!("catch {{ {} edit redo }}", &self.id);
+        wish::tell_wish(&msg);
+    }
+
+    /// Clears the undo and redo stacks.
+    pub fn edit_reset(&self) {
+        let msg = format!("{} edit reset", &self.id);
+        wish::tell_wish(&msg);
+    }
+
+    /// Adds a separator to the undo stack, marking the boundary between
+    /// edits that are undone together.
+    pub fn edit_separator(&self) {
+        let msg = format!("{} edit separator", &self.id);
+        wish::tell_wish(&msg);
+    }
+
+    /// Undoes the last edit, if there is one.
+    pub fn edit_undo(&self) {
+        let msg = format!("catch {{ {} edit undo }}", &self.id);
         wish::tell_wish(&msg);
     }
 
@@ -84,23 +296,22 @@ impl TkText {
     }
 
     /// Get a range of text.
-    pub fn get(
-        &self,
-        (from_line, from_character): (u64, u64),
-        (to_line, to_character): (u64, u64),
-    ) -> String {
+    pub fn get(&self, from: impl Into<TextIndex>, to: impl Into<TextIndex>) -> String {
         let msg = format!(
-            "puts [{} get {}.{} {}.{}] ; flush stdout",
-            &self.id, from_line, from_character, to_line, to_character
+            "puts [{} get {{{}}} {{{}}}] ; flush stdout",
+            &self.id,
+            from.into(),
+            to.into()
         );
         wish::ask_wish(&msg)
     }
 
     /// Get a range of text from a position to end.
-    pub fn get_to_end(&self, (from_line, from_character): (u64, u64)) -> String {
+    pub fn get_to_end(&self, from: impl Into<TextIndex>) -> String {
         let msg = format!(
-            "puts [{} get {}.{} end] ; flush stdout",
-            &self.id, from_line, from_character
+            "puts [{} get {{{}}} end] ; flush stdout",
+            &self.id,
+            from.into()
         );
         wish::ask_wish(&msg)
     }
@@ -110,9 +321,48 @@ impl TkText {
         widget::configure(&self.id, "height", &height.to_string());
     }
 
-    /// Insert at given (line, character) position of text.
-    pub fn insert(&self, (line, character): (u64, u64), text: &str) {
-        let msg = format!("{} insert {}.{} {{{}}}", &self.id, line, character, text);
+    /// Tags every match of pattern in the text with the given tag, first
+    /// removing the tag from the whole text, and returns the number of
+    /// matches: useful for highlighting results in find-in-file dialogs.
+    ///
+    /// The search is made from the start of the text, finding all matches,
+    /// with the other settings taken from options. An error is returned,
+    /// and the tag left unchanged, if the pattern is not a valid regular
+    /// expression.
+    pub fn highlight_all(
+        &self,
+        pattern: &str,
+        tag: &str,
+        options: &SearchOptions,
+    ) -> Result<usize, wish::TkError> {
+        let mut options = options.clone();
+        options.all(true).backwards(false);
+        let matches = self.search_with_options(pattern, (1, 0), &options)?;
+
+        let mut msg = format!("{} tag remove {{{}}} 1.0 end", &self.id, tag);
+        for found in &matches {
+            msg.push_str(&format!(
+                " ; {} tag add {{{}}} {} {}",
+                &self.id, tag, found.start, found.end
+            ));
+        }
+        wish::tell_wish(&msg);
+
+        Ok(matches.len())
+    }
+
+    /// Returns the (line, character) position that the given index refers
+    /// to, or None if there is no such position, e.g. for the start of the
+    /// selection when nothing is selected.
+    pub fn index(&self, index: impl Into<TextIndex>) -> Option<(u64, u64)> {
+        let script = format!("{} index {{{}}}", &self.id, index.into());
+        let result = wish::eval_wish(&script).ok()?;
+        index::parse_position(&result)
+    }
+
+    /// Insert at given position of text.
+    pub fn insert(&self, index: impl Into<TextIndex>, text: &str) {
+        let msg = format!("{} insert {{{}}} {{{}}}", &self.id, index.into(), text);
         wish::tell_wish(&msg);
     }
 
@@ -135,30 +385,30 @@ impl TkText {
         wish::tell_wish(&msg);
     }
 
-    /// Inserts an image at given (line, character) position of text.
-    pub fn insert_image(&self, (line, character): (u64, u64), image: &image::TkImage) {
+    /// Inserts an image at given position of text.
+    pub fn insert_image(&self, index: impl Into<TextIndex>, image: &image::TkImage) {
         let msg = format!(
-            "{} image create {}.{} -image {{{}}}",
-            &self.id, line, character, &image.id
+            "{} image create {{{}}} -image {{{}}}",
+            &self.id,
+            index.into(),
+            &image.id
         );
         wish::tell_wish(&msg);
     }
 
-    /// Inserts a Tk widget at given (line, character) position of text.
-    pub fn insert_widget(&self, (line, character): (u64, u64), widget: &impl widget::TkWidget) {
+    /// Inserts a Tk widget at given position of text.
+    pub fn insert_widget(&self, index: impl Into<TextIndex>, widget: &impl widget::TkWidget) {
         let msg = format!(
-            "{} window create {}.{} -window {{{}}}",
+            "{} window create {{{}}} -window {{{}}}",
             &self.id,
-            line,
-            character,
+            index.into(),
             widget.id()
         );
         wish::tell_wish(&msg);
     }
 
-    /// Insert at given (line, character) position of text,
-    /// with given tags.
-    pub fn insert_with_tags(&self, (line, character): (u64, u64), text: &str, tags: &[&str]) {
+    /// Insert at given position of text, with given tags.
+    pub fn insert_with_tags(&self, index: impl Into<TextIndex>, text: &str, tags: &[&str]) {
         let mut tags_str = String::new();
         for tag in tags {
             tags_str.push('{');
@@ -167,12 +417,35 @@ impl TkText {
             tags_str.push(' ');
         }
         let msg = format!(
-            "{} insert {}.{} {{{}}} {{{}}}",
-            self.id, line, character, text, tags_str
+            "{} insert {{{}}} {{{}}} {{{}}}",
+            self.id,
+            index.into(),
+            text,
+            tags_str
         );
         wish::tell_wish(&msg);
     }
 
+    /// Returns true if the text has been modified since the modified flag
+    /// was last cleared.
+    pub fn is_modified(&self) -> bool {
+        let msg = format!("puts [{} edit modified] ; flush stdout", &self.id);
+        let result = wish::ask_wish(&msg);
+        result.trim() == "1"
+    }
+
+    /// Replaces the content of the text widget with the given items,
+    /// as returned by [dump](TkText::dump).
+    ///
+    /// Any images and windows must already exist.
+    pub fn load(&self, items: &[DumpItem]) -> Result<(), wish::TkError> {
+        let script = format!(
+            "apply {{{{}} {{\n{}\n}}}}",
+            dump::load_script(&self.id, items)
+        );
+        wish::eval_wish(&script).map(|_| ())
+    }
+
     /// Sets named mark's gravity to left.
     pub fn mark_gravity_left(&self, mark: &str) {
         let msg = format!("{} mark gravity {} left", &self.id, mark);
@@ -187,18 +460,7 @@ impl TkText {
 
     /// Returns a (line, character) tuple for the given mark's position.
     pub fn mark_index(&self, mark: &str) -> (u64, u64) {
-        let msg = format!("puts [{} index {}] ; flush stdout", &self.id, mark);
-        let result = wish::ask_wish(&msg);
-        let parts: Vec<&str> = result.split('.').collect();
-        if parts.len() == 2 {
-            let line = parts[0].parse::<u64>().unwrap_or(1);
-            let character = parts[1].parse::<u64>().unwrap_or(0);
-
-            (line, character)
-        } else {
-            // TODO - can this fail?
-            (1, 0)
-        }
+        self.index(mark).unwrap_or((1, 0))
     }
 
     /// Returns a list of all the mark names defined in this text widget.
@@ -209,20 +471,28 @@ impl TkText {
     }
 
     /// Returns name of next mark from given position.
-    pub fn mark_next(&self, (line, character): (u64, u64)) -> String {
-        let msg = format!("{} mark next {}.{}", &self.id, line, character);
+    pub fn mark_next(&self, index: impl Into<TextIndex>) -> String {
+        let msg = format!(
+            "puts [{} mark next {{{}}}] ; flush stdout",
+            &self.id,
+            index.into()
+        );
         wish::ask_wish(&msg)
     }
 
     /// Returns name of previous mark to given position.
-    pub fn mark_previous(&self, (line, character): (u64, u64)) -> String {
-        let msg = format!("{} mark prev {}.{}", &self.id, line, character);
+    pub fn mark_previous(&self, index: impl Into<TextIndex>) -> String {
+        let msg = format!(
+            "puts [{} mark prev {{{}}}] ; flush stdout",
+            &self.id,
+            index.into()
+        );
         wish::ask_wish(&msg)
     }
 
     /// Sets named mark to given position.
-    pub fn mark_set(&self, mark: &str, (line, character): (u64, u64)) {
-        let msg = format!("{} mark set {} {}.{}", &self.id, mark, line, character);
+    pub fn mark_set(&self, mark: &str, index: impl Into<TextIndex>) {
+        let msg = format!("{} mark set {} {{{}}}", &self.id, mark, index.into());
         wish::tell_wish(&msg);
     }
 
@@ -232,6 +502,28 @@ impl TkText {
         wish::tell_wish(&msg);
     }
 
+    /// Maximum number of edits kept on the undo stack: 0 for no limit.
+    pub fn max_undo(&self, value: u64) {
+        widget::configure(&self.id, "maxundo", &value.to_string());
+    }
+
+    /// Sets the function to be called when the modified flag changes.
+    /// This function takes one boolean parameter, which is the _new_ value
+    /// of the flag.
+    ///
+    /// Tk only reports the flag becoming set once: clear the flag with
+    /// [set_modified](TkText::set_modified), e.g. after saving, to be told
+    /// of the next modification.
+    pub fn on_modified(&self, command: impl Fn(bool) + Send + 'static) {
+        let key = wish::callback_key(&self.id, "modified");
+        wish::add_callback1_bool(&key, wish::mk_callback1_bool(command));
+        let msg = format!(
+            "bind {} <<Modified>> {{ puts cb1b-{}-[%W edit modified] ; flush stdout }}",
+            &self.id, key
+        );
+        wish::tell_wish(&msg);
+    }
+
     /// Amount of horizontal padding for widget.
     pub fn padx(&self, value: u64) {
         widget::configure(&self.id, "padx", &value.to_string());
@@ -248,15 +540,13 @@ impl TkText {
     }
 
     /// Replaces a range of text with new text.
-    pub fn replace(
-        &self,
-        (from_line, from_character): (u64, u64),
-        (to_line, to_character): (u64, u64),
-        text: &str,
-    ) {
+    pub fn replace(&self, from: impl Into<TextIndex>, to: impl Into<TextIndex>, text: &str) {
         let msg = format!(
-            "{} replace {}.{} {}.{} {{{}}}",
-            &self.id, from_line, from_character, to_line, to_character, text
+            "{} replace {{{}}} {{{}}} {{{}}}",
+            &self.id,
+            from.into(),
+            to.into(),
+            text
         );
         wish::tell_wish(&msg);
     }
@@ -264,27 +554,63 @@ impl TkText {
     /// Searches the text widget from given position for the
     /// text, returning an Option type containing either the
     /// position of the found text or none.
-    pub fn search(&self, text: &str, (line, character): (u64, u64)) -> Option<(u64, u64)> {
+    pub fn search(&self, text: &str, from: impl Into<TextIndex>) -> Option<(u64, u64)> {
         let msg = format!(
-            "puts [{} search {{{}}} {}.{}] ; flush stdout",
-            &self.id, text, line, character
+            "puts [{} search {{{}}} {{{}}}] ; flush stdout",
+            &self.id,
+            text,
+            from.into()
         );
         let result = wish::ask_wish(&msg);
-        let parts: Vec<&str> = result.split('.').collect();
-        if parts.len() == 2 {
-            let line = parts[0].parse::<u64>().unwrap_or(1);
-            let character = parts[1].parse::<u64>().unwrap_or(0);
+        index::parse_position(&result)
+    }
 
-            Some((line, character))
-        } else {
-            None
-        }
+    /// Searches the text widget from given position for the pattern,
+    /// using the given options, returning the range of each match found.
+    ///
+    /// Without the `all` option, at most one match is returned. An error is
+    /// returned if the pattern is not a valid regular expression.
+    pub fn search_with_options(
+        &self,
+        pattern: &str,
+        from: impl Into<TextIndex>,
+        options: &SearchOptions,
+    ) -> Result<Vec<TextMatch>, wish::TkError> {
+        // -- the end of each match is found from its length, given by -count
+        let script = format!(
+            "apply {{{{}} {{
+                set starts [{} search -count counts {} {} {{{}}} {}]
+                set result {{}}
+                foreach start $starts count $counts {{
+                    lappend result $start [{} index \"$start + $count indices\"]
+                }}
+                return $result
+            }}}}",
+            &self.id,
+            options.switches(),
+            wish::tcl_quote(pattern),
+            from.into(),
+            options.stop(),
+            &self.id
+        );
+        let result = wish::eval_wish(&script)?;
+        Ok(search::parse_matches(&result))
     }
 
-    /// Arranges text widget display to ensure the given line, character
+    /// Arranges text widget display to ensure the given position
     /// is visible.
-    pub fn see(&self, (line, character): (u64, u64)) {
-        let msg = format!("{} see {}.{}", self.id, line, character);
+    pub fn see(&self, index: impl Into<TextIndex>) {
+        let msg = format!("{} see {{{}}}", self.id, index.into());
+        wish::tell_wish(&msg);
+    }
+
+    /// Sets or clears the modified flag.
+    pub fn set_modified(&self, value: bool) {
+        let msg = format!(
+            "{} edit modified {}",
+            &self.id,
+            if value { "1" } else { "0" }
+        );
         wish::tell_wish(&msg);
     }
 
@@ -294,15 +620,13 @@ impl TkText {
     }
 
     /// Associates given tag with text in specified range.
-    pub fn tag_add(
-        &self,
-        tag: &str,
-        (from_line, from_character): (u64, u64),
-        (to_line, to_character): (u64, u64),
-    ) {
+    pub fn tag_add(&self, tag: &str, from: impl Into<TextIndex>, to: impl Into<TextIndex>) {
         let msg = format!(
-            "{} tag add {{{}}} {}.{} {}.{}",
-            &self.id, tag, from_line, from_character, to_line, to_character
+            "{} tag add {{{}}} {{{}}} {{{}}}",
+            &self.id,
+            tag,
+            from.into(),
+            to.into()
         );
         wish::tell_wish(&msg);
     }
@@ -315,7 +639,7 @@ impl TkText {
         command: impl Fn(widget::TkEvent) + Send + 'static,
     ) {
         // id+tag+pattern used as identifier
-        let tag_pattern = format!("{}{}{}", &self.id, tag, pattern);
+        let tag_pattern = wish::callback_key(&self.id, &format!("{}{}", tag, pattern));
         wish::add_callback1_event(&tag_pattern, wish::mk_callback1_event(command));
         let msg = format!(
             "{} tag bind {} {} {{ puts cb1e:{}:%x:%y:%X:%Y:%h:%w:%k:%K:%b ; flush stdout }}",
@@ -330,15 +654,40 @@ impl TkText {
         wish::tell_wish(&msg);
     }
 
+    /// Returns the current value of the given option for a tag, e.g.
+    /// "foreground".
+    pub fn tag_cget(&self, tag: &str, option: &str) -> String {
+        let msg = format!(
+            "puts [{} tag cget {{{}}} -{}] ; flush stdout",
+            &self.id, tag, option
+        );
+        let result = wish::ask_wish(&msg);
+        String::from(result.trim_end_matches('\n'))
+    }
+
     /// Formatting is applied to tags using configuration options.
     ///
     /// For the available options, see the Tk
-    /// [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/text.htm#M43)
+    /// [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/text.htm#M43),
+    /// or use [tag_style](TkText::tag_style) to set several at once.
     pub fn tag_configure(&self, tag: &str, option: &str, value: &str) {
         let msg = format!("{} tag configure {} -{} {}", &self.id, tag, option, value);
         wish::tell_wish(&msg);
     }
 
+    /// Gives the tag the lowest priority of all tags: where tags set the same
+    /// option, the tag with the highest priority is used.
+    pub fn tag_lower(&self, tag: &str) {
+        let msg = format!("{} tag lower {{{}}}", &self.id, tag);
+        wish::tell_wish(&msg);
+    }
+
+    /// Gives the tag a priority just lower than the other tag.
+    pub fn tag_lower_below(&self, tag: &str, other: &str) {
+        let msg = format!("{} tag lower {{{}}} {{{}}}", &self.id, tag, other);
+        wish::tell_wish(&msg);
+    }
+
     /// Returns a list of all the tag names defined in this text widget.
     pub fn tag_names(&self) -> Vec<String> {
         let msg = format!("puts [{} tag names] ; flush stdout", &self.id);
@@ -348,25 +697,95 @@ impl TkText {
 
     /// Returns a list of all the tag names defined in this text widget
     /// at the given location.
-    pub fn tag_names_at(&self, (line, character): (u64, u64)) -> Vec<String> {
+    pub fn tag_names_at(&self, index: impl Into<TextIndex>) -> Vec<String> {
         let msg = format!(
-            "puts [{} tag names {}.{}] ; flush stdout",
-            &self.id, line, character
+            "puts [{} tag names {{{}}}] ; flush stdout",
+            &self.id,
+            index.into()
         );
         let result = wish::ask_wish(&msg);
         wish::split_items(&result)
     }
 
-    /// De-associates given tag with text in specified range.
-    pub fn tag_remove(
+    /// Returns the first range of text with the given tag, starting at or
+    /// after the given position, if there is one.
+    pub fn tag_next_range(
         &self,
         tag: &str,
-        (from_line, from_character): (u64, u64),
-        (to_line, to_character): (u64, u64),
-    ) {
+        from: impl Into<TextIndex>,
+    ) -> Option<(TextIndex, TextIndex)> {
+        let msg = format!(
+            "puts [{} tag nextrange {{{}}} {{{}}}] ; flush stdout",
+            &self.id,
+            tag,
+            from.into()
+        );
+        let result = wish::ask_wish(&msg);
+        index::parse_ranges(&result).into_iter().next()
+    }
+
+    /// Returns the last range of text with the given tag, starting before
+    /// the given position, if there is one.
+    pub fn tag_prev_range(
+        &self,
+        tag: &str,
+        from: impl Into<TextIndex>,
+    ) -> Option<(TextIndex, TextIndex)> {
+        let msg = format!(
+            "puts [{} tag prevrange {{{}}} {{{}}}] ; flush stdout",
+            &self.id,
+            tag,
+            from.into()
+        );
+        let result = wish::ask_wish(&msg);
+        index::parse_ranges(&result).into_iter().next()
+    }
+
+    /// Gives the tag the highest priority of all tags: where tags set the
+    /// same option, the tag with the highest priority is used.
+    pub fn tag_raise(&self, tag: &str) {
+        let msg = format!("{} tag raise {{{}}}", &self.id, tag);
+        wish::tell_wish(&msg);
+    }
+
+    /// Gives the tag a priority just higher than the other tag.
+    pub fn tag_raise_above(&self, tag: &str, other: &str) {
+        let msg = format!("{} tag raise {{{}}} {{{}}}", &self.id, tag, other);
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns every range of text with the given tag, in order.
+    pub fn tag_ranges(&self, tag: &str) -> Vec<(TextIndex, TextIndex)> {
+        let script = format!("{} tag ranges {{{}}}", &self.id, tag);
+        let result = wish::eval_wish(&script).unwrap_or_default();
+        index::parse_ranges(&result)
+    }
+
+    /// De-associates given tag with text in specified range.
+    pub fn tag_remove(&self, tag: &str, from: impl Into<TextIndex>, to: impl Into<TextIndex>) {
+        let msg = format!(
+            "{} tag remove {{{}}} {{{}}} {{{}}}",
+            &self.id,
+            tag,
+            from.into(),
+            to.into()
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Sets whether edits are recorded, so they can be undone.
+    pub fn undo(&self, value: bool) {
+        widget::configure(&self.id, "undo", if value { "1" } else { "0" });
+    }
+
+    /// Sets the formatting of a tag, applying all the options in the style
+    /// together.
+    pub fn tag_style(&self, tag: &str, style: &TextTagStyle) {
         let msg = format!(
-            "{} tag remove {{{}}} {}.{} {}.{}",
-            &self.id, tag, from_line, from_character, to_line, to_character
+            "{} tag configure {{{}}} {}",
+            &self.id,
+            tag,
+            style.options_string()
         );
         wish::tell_wish(&msg);
     }
@@ -380,4 +799,51 @@ impl TkText {
     pub fn wrap(&self, value: widget::Wrapping) {
         widget::configure(&self.id, "wrap", &value.to_string());
     }
+
+    /// Returns the visible horizontal part of the text, as a pair of
+    /// fractions (first, last) in the range 0 to 1.
+    pub fn x_view(&self) -> (f64, f64) {
+        let msg = format!("puts [{} xview] ; flush stdout", &self.id);
+        let result = wish::ask_wish(&msg);
+        widget::view_fractions(&result)
+    }
+
+    /// Scrolls view so given fraction of the text's width is off-screen
+    /// to the left.
+    pub fn x_view_moveto(&self, fraction: f64) {
+        let msg = format!("{} xview moveto {}", &self.id, fraction);
+        wish::tell_wish(&msg);
+    }
+
+    /// Scrolls view left (negative) or right (positive) by given amount.
+    pub fn x_view_scroll(&self, amount: i64, units: widget::ScrollUnits) {
+        let msg = format!("{} xview scroll {} {}", &self.id, amount, units);
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns the visible vertical part of the text, as a pair of
+    /// fractions (first, last) in the range 0 to 1.
+    pub fn y_view(&self) -> (f64, f64) {
+        let msg = format!("puts [{} yview] ; flush stdout", &self.id);
+        let result = wish::ask_wish(&msg);
+        widget::view_fractions(&result)
+    }
+
+    /// Scrolls view so given fraction of the text is off-screen to the top.
+    pub fn y_view_moveto(&self, fraction: f64) {
+        let msg = format!("{} yview moveto {}", &self.id, fraction);
+        wish::tell_wish(&msg);
+    }
+
+    /// Scrolls view up (negative) or down (positive) by given amount.
+    pub fn y_view_scroll(&self, amount: i64, units: widget::ScrollUnits) {
+        let msg = format!("{} yview scroll {} {}", &self.id, amount, units);
+        wish::tell_wish(&msg);
+    }
+
+    /// Scrolls view so the line holding the given position is at the top.
+    pub fn y_view_to(&self, index: impl Into<TextIndex>) {
+        let msg = format!("{} yview {{{}}}", &self.id, index.into());
+        wish::tell_wish(&msg);
+    }
 }
@@ -0,0 +1,326 @@
+//! Text dump - the structured contents of a text widget.
+//!
+//! [dump](crate::TkText::dump) returns the contents of a range of text as a
+//! list of [DumpItem]s: runs of text, together with where tags start and
+//! end, and the positions of marks, images and windows. The items can be
+//! given back to [load](crate::TkText::load) to rebuild the content.
+//!
+//! # Saving documents
+//!
+//! The items can be written to a simple line-based markup with
+//! [to_markup], and read back with [from_markup], so styled content can be
+//! saved to a file and reopened exactly:
+//!
+//! ```ignore
+//! let items = text.dump((1, 0), TextIndex::End, &[])?;
+//! std::fs::write("notes.txt", rish::dump::to_markup(&items))?;
+//!
+//! let saved = std::fs::read_to_string("notes.txt")?;
+//! text.load(&rish::dump::from_markup(&saved)?)?;
+//! ```
+//!
+//! Each item is one line, of the form `KIND INDEX VALUE`, where the value
+//! has any backslashes, newlines and carriage returns escaped as `\\`, `\n`
+//! and `\r`.
+//!
+//! The formatting of tags is not part of the dump: the application should
+//! configure its tags before loading.
+//!
+//! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/text.htm#M87)
+
+use std::fmt;
+
+use super::index::{self, TextIndex};
+use crate::wish;
+
+/// Kinds of item to include in a dump.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DumpKind {
+    Image,
+    Mark,
+    Tag,
+    Text,
+    Window,
+}
+
+impl fmt::Display for DumpKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = match self {
+            DumpKind::Image => "image",
+            DumpKind::Mark => "mark",
+            DumpKind::Tag => "tag",
+            DumpKind::Text => "text",
+            DumpKind::Window => "window",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
+/// An item of content in a text widget, with its position.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DumpItem {
+    /// embedded image, naming the Tk image displayed
+    Image { name: String, index: TextIndex },
+    /// mark, by name
+    Mark { name: String, index: TextIndex },
+    /// end of a range of text with the named tag
+    TagOff { name: String, index: TextIndex },
+    /// start of a range of text with the named tag
+    TagOn { name: String, index: TextIndex },
+    /// run of text, with the same tags throughout
+    Text { text: String, index: TextIndex },
+    /// embedded window, naming the widget displayed
+    Window { name: String, index: TextIndex },
+}
+
+impl DumpItem {
+    // Creates an item from its tcl key, value and index.
+    fn new(key: &str, value: String, index: TextIndex) -> Option<DumpItem> {
+        let item = match key {
+            "image" => DumpItem::Image { name: value, index },
+            "mark" => DumpItem::Mark { name: value, index },
+            "tagoff" => DumpItem::TagOff { name: value, index },
+            "tagon" => DumpItem::TagOn { name: value, index },
+            "text" => DumpItem::Text { text: value, index },
+            "window" => DumpItem::Window { name: value, index },
+            _ => return None,
+        };
+
+        Some(item)
+    }
+
+    // Returns the tcl key, value and index of this item.
+    fn parts(&self) -> (&str, &str, &TextIndex) {
+        match self {
+            DumpItem::Image { name, index } => ("image", name, index),
+            DumpItem::Mark { name, index } => ("mark", name, index),
+            DumpItem::TagOff { name, index } => ("tagoff", name, index),
+            DumpItem::TagOn { name, index } => ("tagon", name, index),
+            DumpItem::Text { text, index } => ("text", text, index),
+            DumpItem::Window { name, index } => ("window", name, index),
+        }
+    }
+}
+
+/// Writes the items as markup, one line per item.
+pub fn to_markup(items: &[DumpItem]) -> String {
+    let mut result = String::new();
+
+    for item in items {
+        let (key, value, index) = item.parts();
+        let value = value
+            .replace('\\', "\\\\")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r");
+        result.push_str(&format!("{} {} {}\n", key, index, value));
+    }
+
+    result
+}
+
+/// Reads items from markup written by [to_markup], returning an error
+/// naming the first line that cannot be read.
+pub fn from_markup(text: &str) -> Result<Vec<DumpItem>, wish::TkError> {
+    let mut result = vec![];
+
+    for (n, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let parts: Vec<&str> = line.splitn(3, ' ').collect();
+        let item = if parts.len() == 3 {
+            index::parse_position(parts[1]).and_then(|position| {
+                DumpItem::new(parts[0], unescape(parts[2]), TextIndex::from(position))
+            })
+        } else {
+            None
+        };
+        match item {
+            Some(item) => result.push(item),
+            None => {
+                let message = format!("Invalid text markup on line {}: {}", n + 1, line);
+                return Err(wish::TkError::new(&message));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+// Reverses the escaping of values done in to_markup.
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some(c) => result.push(c),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+// Converts the flat tcl list of key, value, index triples returned by
+// tk's dump command into items.
+pub(super) fn parse_dump(text: &str) -> Vec<DumpItem> {
+    let parts = wish::split_list(text);
+    let mut result = vec![];
+
+    for triple in parts.chunks_exact(3) {
+        if let Some(position) = index::parse_position(&triple[2]) {
+            if let Some(item) =
+                DumpItem::new(&triple[0], triple[1].clone(), TextIndex::from(position))
+            {
+                result.push(item);
+            }
+        }
+    }
+
+    result
+}
+
+// Returns a tcl script which rebuilds the content of text widget `id`
+// from the items. Text is inserted with the tags open at that point; marks
+// are set once all the text is in place, so later insertions do not move
+// them.
+pub(super) fn load_script(id: &str, items: &[DumpItem]) -> String {
+    let mut script = format!("{} delete 1.0 end\nset tags {{}}\nset marks {{}}\n", id);
+    let mut ends_in_newline = false;
+
+    for item in items {
+        match item {
+            DumpItem::Image { name, .. } => {
+                script.push_str(&format!(
+                    "{} image create end -image {}\n",
+                    id,
+                    wish::tcl_quote(name)
+                ));
+                script.push_str(&format!(
+                    "foreach tag $tags {{ {} tag add $tag {{end -2 chars}} }}\n",
+                    id
+                ));
+                ends_in_newline = false;
+            }
+            DumpItem::Mark { name, .. } => {
+                if name != "current" {
+                    script.push_str(&format!(
+                        "lappend marks {} [{} index {{end -1 chars}}]\n",
+                        wish::tcl_quote(name),
+                        id
+                    ));
+                }
+            }
+            DumpItem::TagOff { name, .. } => {
+                script.push_str(&format!(
+                    "set tags [lsearch -all -inline -not -exact $tags {}]\n",
+                    wish::tcl_quote(name)
+                ));
+            }
+            DumpItem::TagOn { name, .. } => {
+                script.push_str(&format!("lappend tags {}\n", wish::tcl_quote(name)));
+            }
+            DumpItem::Text { text, .. } => {
+                if !text.is_empty() {
+                    script.push_str(&format!(
+                        "{} insert end {} $tags\n",
+                        id,
+                        wish::tcl_quote(text)
+                    ));
+                    ends_in_newline = text.ends_with('\n');
+                }
+            }
+            DumpItem::Window { name, .. } => {
+                script.push_str(&format!(
+                    "{} window create end -window {}\n",
+                    id,
+                    wish::tcl_quote(name)
+                ));
+                script.push_str(&format!(
+                    "foreach tag $tags {{ {} tag add $tag {{end -2 chars}} }}\n",
+                    id
+                ));
+                ends_in_newline = false;
+            }
+        }
+    }
+
+    // -- the widget always ends in a newline, so remove the extra one
+    // copied from the end of a dump
+    if ends_in_newline {
+        script.push_str(&format!("{} delete {{end -2 chars}}\n", id));
+    }
+    script.push_str(&format!(
+        "foreach {{name index}} $marks {{ {} mark set $name $index }}",
+        id
+    ));
+
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dump_parse() {
+        let items =
+            parse_dump("tagon bold 1.0 text {Hello {x}} 1.0 tagoff bold 1.9 mark insert 2.0");
+        assert_eq!(
+            vec![
+                DumpItem::TagOn {
+                    name: String::from("bold"),
+                    index: TextIndex::LineChar(1, 0)
+                },
+                DumpItem::Text {
+                    text: String::from("Hello {x}"),
+                    index: TextIndex::LineChar(1, 0)
+                },
+                DumpItem::TagOff {
+                    name: String::from("bold"),
+                    index: TextIndex::LineChar(1, 9)
+                },
+                DumpItem::Mark {
+                    name: String::from("insert"),
+                    index: TextIndex::LineChar(2, 0)
+                },
+            ],
+            items
+        );
+    }
+
+    #[test]
+    fn markup_round_trip() {
+        let items = vec![
+            DumpItem::TagOn {
+                name: String::from("heading"),
+                index: TextIndex::LineChar(1, 0),
+            },
+            DumpItem::Text {
+                text: String::from("Notes \\ to self\n\r"),
+                index: TextIndex::LineChar(1, 0),
+            },
+            DumpItem::Image {
+                name: String::from("icon"),
+                index: TextIndex::LineChar(2, 0),
+            },
+        ];
+        let markup = to_markup(&items);
+        assert_eq!(3, markup.lines().count());
+        assert_eq!(items, from_markup(&markup).unwrap());
+    }
+
+    #[test]
+    fn markup_errors() {
+        assert!(from_markup("text 1.0 fine\n\nbold 1.0 unknown").is_err());
+        assert!(from_markup("text end missing index").is_err());
+        assert!(from_markup("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,492 @@
+//! Syntax highlighting - styles the text in a text widget as it is edited.
+//!
+//! A [Highlighter] splits each line of text into ranges, each with the name
+//! of a style, such as "keyword" or "comment". A [Theme] gives the
+//! formatting for each style, which is applied to the text using tags.
+//!
+//! ```ignore
+//! use rish::highlight::{self, Lexer, Theme};
+//!
+//! let editor = rish::make_text(&root);
+//! highlight::attach(&editor, Lexer::rust(), &Theme::default());
+//! ```
+//!
+//! # Lexers
+//!
+//! [Lexer] provides simple highlighting for Rust, JSON, TOML and Tcl, and
+//! can be set up for other languages with similar syntax. Lexers work one
+//! line at a time, so constructs spanning lines, such as block comments,
+//! are not recognised.
+//!
+//! # Re-highlighting
+//!
+//! After the text is changed, only the lines affected by the change are
+//! highlighted again, once pending edits are done. Changes are tracked with
+//! a trace on the widget's insert, delete, replace and edit commands, rather
+//! than with the `<<Modified>>` event: that event only fires when the
+//! modified flag is set, so tracking each edit would mean clearing the flag,
+//! and [on_modified](crate::TkText::on_modified) could no longer report
+//! whether the text is unsaved.
+//!
+
+use std::ops::Range;
+
+use super::{TextTagStyle, TkText};
+use crate::wish;
+
+/// Splits lines of text into styled ranges.
+pub trait Highlighter: Send {
+    /// Returns the ranges in line to style, as character (not byte)
+    /// offsets, with the name of the style for each range.
+    fn highlight_line(&self, line: &str) -> Vec<(Range<usize>, String)>;
+}
+
+/// A simple lexer, styling comments, strings, numbers, keywords and
+/// identifiers, configured for a language.
+///
+/// The styles used are: "comment", "key", "keyword", "macro", "number",
+/// "section", "string", "type" and "variable".
+///
+/// The methods on this struct set the values of different options in a
+/// builder style, starting from [Lexer::default] or a language.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Lexer {
+    keys: bool,
+    keywords: Vec<String>,
+    line_comments: Vec<String>,
+    quotes: Vec<char>,
+    sections: bool,
+    types: bool,
+    variable_prefix: Option<char>,
+}
+
+impl Lexer {
+    /// Lexer for JSON.
+    pub fn json() -> Lexer {
+        let mut lexer = Lexer::default();
+        lexer
+            .keys(true)
+            .keywords(&["true", "false", "null"])
+            .quote('"');
+        lexer
+    }
+
+    /// Lexer for Rust.
+    pub fn rust() -> Lexer {
+        let mut lexer = Lexer::default();
+        lexer
+            .keywords(&[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+                "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+            ])
+            .line_comment("//")
+            .quote('"')
+            .types(true);
+        lexer
+    }
+
+    /// Lexer for Tcl.
+    pub fn tcl() -> Lexer {
+        let mut lexer = Lexer::default();
+        lexer
+            .keywords(&[
+                "after",
+                "append",
+                "break",
+                "catch",
+                "continue",
+                "else",
+                "elseif",
+                "error",
+                "expr",
+                "for",
+                "foreach",
+                "global",
+                "if",
+                "incr",
+                "lappend",
+                "lassign",
+                "list",
+                "namespace",
+                "proc",
+                "puts",
+                "return",
+                "set",
+                "switch",
+                "unset",
+                "upvar",
+                "variable",
+                "while",
+            ])
+            .line_comment("#")
+            .quote('"')
+            .variable_prefix('$');
+        lexer
+    }
+
+    /// Lexer for TOML.
+    pub fn toml() -> Lexer {
+        let mut lexer = Lexer::default();
+        lexer
+            .keys(true)
+            .keywords(&["true", "false"])
+            .line_comment("#")
+            .quote('"')
+            .quote('\'')
+            .sections(true);
+        lexer
+    }
+
+    /// Styles strings and words followed by `:` or `=` as keys.
+    pub fn keys(&mut self, value: bool) -> &mut Self {
+        self.keys = value;
+        self
+    }
+
+    /// Adds words to style as keywords.
+    pub fn keywords(&mut self, words: &[&str]) -> &mut Self {
+        self.keywords
+            .extend(words.iter().map(|word| String::from(*word)));
+        self
+    }
+
+    /// Adds a prefix which starts a comment running to the end of the line.
+    pub fn line_comment(&mut self, prefix: &str) -> &mut Self {
+        self.line_comments.push(String::from(prefix));
+        self
+    }
+
+    /// Adds a character which starts and ends a string: within a string,
+    /// a backslash escapes the following character.
+    pub fn quote(&mut self, value: char) -> &mut Self {
+        self.quotes.push(value);
+        self
+    }
+
+    /// Styles lines starting with `[` as section headers.
+    pub fn sections(&mut self, value: bool) -> &mut Self {
+        self.sections = value;
+        self
+    }
+
+    /// Styles capitalised words as types, and words followed by `!` as
+    /// macros.
+    pub fn types(&mut self, value: bool) -> &mut Self {
+        self.types = value;
+        self
+    }
+
+    /// Sets a character which starts a variable name, such as `$`.
+    pub fn variable_prefix(&mut self, value: char) -> &mut Self {
+        self.variable_prefix = Some(value);
+        self
+    }
+
+    // Returns true if prefix appears in chars at position from.
+    fn starts_with_at(chars: &[char], from: usize, prefix: &str) -> bool {
+        prefix
+            .chars()
+            .enumerate()
+            .all(|(n, c)| chars.get(from + n) == Some(&c))
+    }
+
+    // Returns true if a key separator is the next non-space character.
+    fn followed_by_separator(chars: &[char], from: usize) -> bool {
+        chars[from..]
+            .iter()
+            .find(|c| !c.is_whitespace())
+            .is_some_and(|c| *c == ':' || *c == '=')
+    }
+}
+
+impl Highlighter for Lexer {
+    fn highlight_line(&self, line: &str) -> Vec<(Range<usize>, String)> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut result = vec![];
+
+        if self.sections {
+            let start = chars.iter().take_while(|c| c.is_whitespace()).count();
+            if chars.get(start) == Some(&'[') {
+                let end =
+                    chars.len() - chars.iter().rev().take_while(|c| c.is_whitespace()).count();
+                result.push((start..end, String::from("section")));
+                return result;
+            }
+        }
+
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let start = i;
+
+            if self
+                .line_comments
+                .iter()
+                .any(|prefix| Lexer::starts_with_at(&chars, i, prefix))
+            {
+                result.push((i..chars.len(), String::from("comment")));
+                break;
+            } else if self.quotes.contains(&c) {
+                i += 1;
+                while i < chars.len() && chars[i] != c {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(chars.len());
+                let style = if self.keys && Lexer::followed_by_separator(&chars, i) {
+                    "key"
+                } else {
+                    "string"
+                };
+                result.push((start..i, String::from(style)));
+            } else if c.is_ascii_digit() {
+                while i < chars.len() && (is_word(chars[i]) || chars[i] == '.') {
+                    i += 1;
+                }
+                result.push((start..i, String::from("number")));
+            } else if Some(c) == self.variable_prefix
+                && chars.get(i + 1).is_some_and(|c| is_word(*c))
+            {
+                i += 1;
+                while i < chars.len() && (is_word(chars[i]) || chars[i] == ':') {
+                    i += 1;
+                }
+                result.push((start..i, String::from("variable")));
+            } else if is_word(c) {
+                while i < chars.len() && is_word(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                if self.keywords.contains(&word) {
+                    result.push((start..i, String::from("keyword")));
+                } else if self.keys && Lexer::followed_by_separator(&chars, i) {
+                    result.push((start..i, String::from("key")));
+                } else if self.types && chars.get(i) == Some(&'!') {
+                    i += 1;
+                    result.push((start..i, String::from("macro")));
+                } else if self.types && c.is_uppercase() {
+                    result.push((start..i, String::from("type")));
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        result
+    }
+}
+
+/// Gives the formatting for each style, as a [TextTagStyle].
+#[derive(Clone, Debug)]
+pub struct Theme {
+    styles: Vec<(String, TextTagStyle)>,
+}
+
+impl Default for Theme {
+    /// A light theme, covering the styles used by [Lexer].
+    fn default() -> Self {
+        let mut theme = Theme { styles: vec![] };
+        theme
+            .style("comment", TextTagStyle::default().foreground("#6a737d"))
+            .style("key", TextTagStyle::default().foreground("#005cc5"))
+            .style("keyword", TextTagStyle::default().foreground("#d73a49"))
+            .style("macro", TextTagStyle::default().foreground("#6f42c1"))
+            .style("number", TextTagStyle::default().foreground("#e36209"))
+            .style("section", TextTagStyle::default().foreground("#22863a"))
+            .style("string", TextTagStyle::default().foreground("#032f62"))
+            .style("type", TextTagStyle::default().foreground("#6f42c1"))
+            .style("variable", TextTagStyle::default().foreground("#e36209"));
+        theme
+    }
+}
+
+impl Theme {
+    /// Sets the formatting used for the named style, replacing any
+    /// formatting already set for it.
+    pub fn style(&mut self, name: &str, style: &TextTagStyle) -> &mut Self {
+        self.styles.retain(|(existing, _)| existing != name);
+        self.styles.push((String::from(name), style.clone()));
+        self
+    }
+}
+
+// Prefix of the tags used for each style.
+const TAG_PREFIX: &str = "rish_highlight_";
+
+/// Highlights the text widget using the given highlighter and theme, now
+/// and after each change to its text. Should be called once per widget.
+///
+/// The highlighting tags are placed below all other tags, so the selection
+/// and application tags still show.
+pub fn attach(text: &TkText, highlighter: impl Highlighter + 'static, theme: &Theme) {
+    let mut msg = String::new();
+    for (name, style) in &theme.styles {
+        msg.push_str(&format!(
+            "{} tag configure {}{} {} ; {} tag lower {}{} ; ",
+            &text.id,
+            TAG_PREFIX,
+            name,
+            style.options_string(),
+            &text.id,
+            TAG_PREFIX,
+            name
+        ));
+    }
+
+    let id = text.id.clone();
+    let key = wish::callback_key(&text.id, "highlight");
+    wish::add_callback0(
+        &key,
+        wish::mk_callback0(move || highlight_changes(&id, &highlighter)),
+    );
+
+    // -- highlight everything to start with
+    msg.push_str(&format!(
+        "trace add execution {} enter rish_text_enter ; \
+         trace add execution {} leave rish_text_leave ; \
+         set rish_text_dirty({}) [list 1 [lindex [split [{} index end] .] 0]] ; \
+         after idle [list rish_text_report {}]",
+        &text.id, &text.id, &text.id, &text.id, &text.id
+    ));
+    wish::tell_wish(&msg);
+}
+
+// Highlights the lines changed since the last call, fetching their
+// text and replacing the highlighting tags on them.
+fn highlight_changes(id: &str, highlighter: &impl Highlighter) {
+    let script = format!(
+        "apply {{{{}} {{
+            global rish_text_dirty
+            if {{![info exists rish_text_dirty({})]}} {{ return {{}} }}
+            lassign $rish_text_dirty({}) first last
+            unset rish_text_dirty({})
+            set end [lindex [split [{} index {{end -1 chars}}] .] 0]
+            set last [expr {{min($last, $end)}}]
+            return [list $first [{} get $first.0 \"$last.0 lineend\"]]
+        }}}}",
+        id, id, id, id, id
+    );
+    let parts = wish::split_list(&wish::eval_wish(&script).unwrap_or_default());
+    if let [first, text] = &parts[..] {
+        let first = first.parse::<usize>().unwrap_or(1);
+        let msg = highlight_script(id, first, text, highlighter);
+        wish::tell_wish(&msg);
+    }
+}
+
+// Returns a tcl script which replaces the highlighting tags on the lines of
+// text, starting from line first.
+fn highlight_script(id: &str, first: usize, text: &str, highlighter: &impl Highlighter) -> String {
+    let last = first + text.matches('\n').count();
+    let mut script = format!(
+        "foreach tag [lsearch -all -inline -glob [{} tag names] {}*] {{ {} tag remove $tag {}.0 {{{}.0 lineend}} }}",
+        id, TAG_PREFIX, id, first, last
+    );
+
+    // -- group ranges by style, to add each tag once
+    let mut styles: Vec<(String, String)> = vec![];
+    for (n, line) in text.split('\n').enumerate() {
+        for (range, style) in highlighter.highlight_line(line) {
+            let ranges = format!(" {}.{} {}.{}", first + n, range.start, first + n, range.end);
+            match styles.iter_mut().find(|(name, _)| *name == style) {
+                Some((_, existing)) => existing.push_str(&ranges),
+                None => styles.push((style, ranges)),
+            }
+        }
+    }
+    for (style, ranges) in styles {
+        script.push_str(&format!(
+            " ; {} tag add {}{}{}",
+            id, TAG_PREFIX, style, ranges
+        ));
+    }
+
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn styles(lexer: &Lexer, line: &str) -> Vec<(String, String)> {
+        let chars: Vec<char> = line.chars().collect();
+        lexer
+            .highlight_line(line)
+            .into_iter()
+            .map(|(range, style)| (chars[range].iter().collect(), style))
+            .collect()
+    }
+
+    fn expected(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(text, style)| (String::from(*text), String::from(*style)))
+            .collect()
+    }
+
+    #[test]
+    fn highlight_rust() {
+        assert_eq!(
+            expected(&[
+                ("let", "keyword"),
+                ("Vec", "type"),
+                ("vec!", "macro"),
+                ("1", "number"),
+                ("\"a \\\" b\"", "string"),
+                ("// é done", "comment"),
+            ]),
+            styles(
+                &Lexer::rust(),
+                "let v: Vec<_> = vec![1, \"a \\\" b\"]; // é done"
+            )
+        );
+    }
+
+    #[test]
+    fn highlight_json() {
+        assert_eq!(
+            expected(&[
+                ("\"name\"", "key"),
+                ("\"rish\"", "string"),
+                ("\"ok\"", "key"),
+                ("true", "keyword"),
+                ("\"v\"", "key"),
+                ("2.5", "number"),
+            ]),
+            styles(
+                &Lexer::json(),
+                "{\"name\": \"rish\", \"ok\": true, \"v\" : 2.5}"
+            )
+        );
+    }
+
+    #[test]
+    fn highlight_toml_and_tcl() {
+        assert_eq!(
+            expected(&[("[package]", "section")]),
+            styles(&Lexer::toml(), "  [package]  ")
+        );
+        assert_eq!(
+            expected(&[("name", "key"), ("'rish'", "string"), ("# note", "comment")]),
+            styles(&Lexer::toml(), "name = 'rish' # note")
+        );
+        assert_eq!(
+            expected(&[
+                ("set", "keyword"),
+                ("$ns::total", "variable"),
+                ("\"$x\"", "string")
+            ]),
+            styles(&Lexer::tcl(), "set y $ns::total; \"$x\"")
+        );
+    }
+
+    #[test]
+    fn highlight_script_ranges() {
+        let script = highlight_script(".t", 3, "fn a\n\nfn b", &Lexer::rust());
+        assert!(script.contains("remove $tag 3.0 {5.0 lineend}"));
+        assert!(script.ends_with(".t tag add rish_highlight_keyword 3.0 3.2 5.0 5.2"));
+    }
+}
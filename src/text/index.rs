@@ -0,0 +1,282 @@
+//! Text indices - positions within a text widget.
+//!
+//! A [TextIndex] describes a position in a text widget, either directly as a
+//! (line, character) pair, or relative to something Tk tracks for the widget,
+//! such as the insertion cursor, a mark, a tag or a point on screen. Any index
+//! can be adjusted by a sequence of modifiers:
+//!
+//! ```ignore
+//! // -- start of the line holding the insertion cursor
+//! text.get(TextIndex::Insert.line_start(), TextIndex::Insert);
+//!
+//! // -- three characters before the end of the text
+//! text.delete(TextIndex::End.chars(-3), TextIndex::End);
+//! ```
+//!
+//! Every [TkText](crate::TkText) method accepting a position takes
+//! `impl Into<TextIndex>`, so (line, character) tuples and mark names can be
+//! used directly.
+//!
+//! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/text.htm#M7)
+
+use std::fmt;
+
+/// Refers to a position in a text widget.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextIndex {
+    /// character covering the given (x, y) pixel position in the widget
+    At(i64, i64),
+    /// character under the mouse pointer
+    Current,
+    /// position just after the last newline
+    End,
+    /// character just after the insertion cursor
+    Insert,
+    /// character at the given (line, character) position: lines count from 1,
+    /// characters from 0
+    LineChar(u64, u64),
+    /// character just after the named mark
+    Mark(String),
+    /// given index, adjusted by the list of modifiers in order
+    Modified(Box<TextIndex>, Vec<IndexModifier>),
+    /// first character of the selection
+    SelFirst,
+    /// character just after the selection
+    SelLast,
+    /// first character with the named tag
+    TagFirst(String),
+    /// character just after the last one with the named tag
+    TagLast(String),
+}
+
+/// Adjusts a [TextIndex].
+///
+/// Counts may be negative, to move backwards. The `Display` variants count
+/// what is displayed, so skip elided text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IndexModifier {
+    Chars(i64),
+    DisplayChars(i64),
+    DisplayIndices(i64),
+    DisplayLineEnd,
+    DisplayLines(i64),
+    DisplayLineStart,
+    Indices(i64),
+    LineEnd,
+    Lines(i64),
+    LineStart,
+    WordEnd,
+    WordStart,
+}
+
+impl fmt::Display for IndexModifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IndexModifier::Chars(n) => write!(f, "{:+} chars", n),
+            IndexModifier::DisplayChars(n) => write!(f, "{:+} display chars", n),
+            IndexModifier::DisplayIndices(n) => write!(f, "{:+} display indices", n),
+            IndexModifier::DisplayLineEnd => write!(f, "display lineend"),
+            IndexModifier::DisplayLines(n) => write!(f, "{:+} display lines", n),
+            IndexModifier::DisplayLineStart => write!(f, "display linestart"),
+            IndexModifier::Indices(n) => write!(f, "{:+} indices", n),
+            IndexModifier::LineEnd => write!(f, "lineend"),
+            IndexModifier::Lines(n) => write!(f, "{:+} lines", n),
+            IndexModifier::LineStart => write!(f, "linestart"),
+            IndexModifier::WordEnd => write!(f, "wordend"),
+            IndexModifier::WordStart => write!(f, "wordstart"),
+        }
+    }
+}
+
+impl fmt::Display for TextIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextIndex::At(x, y) => write!(f, "@{},{}", x, y),
+            TextIndex::Current => write!(f, "current"),
+            TextIndex::End => write!(f, "end"),
+            TextIndex::Insert => write!(f, "insert"),
+            TextIndex::LineChar(line, character) => write!(f, "{}.{}", line, character),
+            TextIndex::Mark(name) => write!(f, "{}", name),
+            TextIndex::Modified(base, modifiers) => {
+                write!(f, "{}", base)?;
+                for modifier in modifiers {
+                    write!(f, " {}", modifier)?;
+                }
+                Ok(())
+            }
+            TextIndex::SelFirst => write!(f, "sel.first"),
+            TextIndex::SelLast => write!(f, "sel.last"),
+            TextIndex::TagFirst(tag) => write!(f, "{}.first", tag),
+            TextIndex::TagLast(tag) => write!(f, "{}.last", tag),
+        }
+    }
+}
+
+impl From<(u64, u64)> for TextIndex {
+    fn from((line, character): (u64, u64)) -> Self {
+        TextIndex::LineChar(line, character)
+    }
+}
+
+/// A string is used as a mark name, or any other index expression Tk
+/// understands, such as "1.0 lineend".
+impl From<&str> for TextIndex {
+    fn from(name: &str) -> Self {
+        TextIndex::Mark(String::from(name))
+    }
+}
+
+impl From<&TextIndex> for TextIndex {
+    fn from(index: &TextIndex) -> Self {
+        index.clone()
+    }
+}
+
+impl TextIndex {
+    /// Moves forward (or backward, if negative) by count characters.
+    pub fn chars(self, count: i64) -> Self {
+        self.modify(IndexModifier::Chars(count))
+    }
+
+    /// Moves forward (or backward) by count displayed characters.
+    pub fn display_chars(self, count: i64) -> Self {
+        self.modify(IndexModifier::DisplayChars(count))
+    }
+
+    /// Moves forward (or backward) by count displayed indices.
+    pub fn display_indices(self, count: i64) -> Self {
+        self.modify(IndexModifier::DisplayIndices(count))
+    }
+
+    /// Moves to the end of the displayed line.
+    pub fn display_line_end(self) -> Self {
+        self.modify(IndexModifier::DisplayLineEnd)
+    }
+
+    /// Moves to the start of the displayed line.
+    pub fn display_line_start(self) -> Self {
+        self.modify(IndexModifier::DisplayLineStart)
+    }
+
+    /// Moves forward (or backward) by count displayed lines, keeping the
+    /// same pixel position within the line where possible.
+    pub fn display_lines(self, count: i64) -> Self {
+        self.modify(IndexModifier::DisplayLines(count))
+    }
+
+    /// Moves forward (or backward) by count indices: unlike characters,
+    /// embedded images and windows are counted.
+    pub fn indices(self, count: i64) -> Self {
+        self.modify(IndexModifier::Indices(count))
+    }
+
+    /// Moves to the last character of the line (the newline).
+    pub fn line_end(self) -> Self {
+        self.modify(IndexModifier::LineEnd)
+    }
+
+    /// Moves to the first character of the line.
+    pub fn line_start(self) -> Self {
+        self.modify(IndexModifier::LineStart)
+    }
+
+    /// Moves forward (or backward) by count lines, keeping the same
+    /// character position within the line where possible.
+    pub fn lines(self, count: i64) -> Self {
+        self.modify(IndexModifier::Lines(count))
+    }
+
+    /// Moves to the character just after the end of the current word.
+    pub fn word_end(self) -> Self {
+        self.modify(IndexModifier::WordEnd)
+    }
+
+    /// Moves to the first character of the current word.
+    pub fn word_start(self) -> Self {
+        self.modify(IndexModifier::WordStart)
+    }
+
+    // Adds modifier to end of list of modifiers.
+    fn modify(self, modifier: IndexModifier) -> Self {
+        match self {
+            TextIndex::Modified(base, mut modifiers) => {
+                modifiers.push(modifier);
+                TextIndex::Modified(base, modifiers)
+            }
+            index => TextIndex::Modified(Box::new(index), vec![modifier]),
+        }
+    }
+}
+
+// Parses a "line.character" position, as returned by tk.
+pub(super) fn parse_position(text: &str) -> Option<(u64, u64)> {
+    let (line, character) = text.trim().split_once('.')?;
+    let line = line.parse::<u64>().ok()?;
+    let character = character.parse::<u64>().ok()?;
+
+    Some((line, character))
+}
+
+// Parses a list of "line.character" positions into (start, end) pairs.
+pub(super) fn parse_ranges(text: &str) -> Vec<(TextIndex, TextIndex)> {
+    let positions: Vec<&str> = text.split_whitespace().collect();
+    let mut result = vec![];
+
+    for pair in positions.chunks_exact(2) {
+        if let (Some(start), Some(end)) = (parse_position(pair[0]), parse_position(pair[1])) {
+            result.push((TextIndex::from(start), TextIndex::from(end)));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_display() {
+        assert_eq!("3.14", TextIndex::from((3, 14)).to_string());
+        assert_eq!("sel.first", TextIndex::SelFirst.to_string());
+        assert_eq!("@10,-2", TextIndex::At(10, -2).to_string());
+        assert_eq!(
+            "found.last",
+            TextIndex::TagLast(String::from("found")).to_string()
+        );
+        assert_eq!("anchor", TextIndex::from("anchor").to_string());
+    }
+
+    #[test]
+    fn index_modifiers() {
+        assert_eq!(
+            "insert linestart +3 chars",
+            TextIndex::Insert.line_start().chars(3).to_string()
+        );
+        assert_eq!(
+            "end -1 display indices",
+            TextIndex::End.display_indices(-1).to_string()
+        );
+        assert_eq!(
+            "2.0 +1 lines wordend",
+            TextIndex::from((2, 0)).lines(1).word_end().to_string()
+        );
+    }
+
+    #[test]
+    fn parse_positions() {
+        assert_eq!(Some((12, 0)), parse_position("12.0\n"));
+        assert_eq!(Some((1, 345)), parse_position("1.345"));
+        assert_eq!(None, parse_position(""));
+        assert_eq!(None, parse_position("end"));
+    }
+
+    #[test]
+    fn parse_position_ranges() {
+        assert!(parse_ranges("").is_empty());
+        assert_eq!(
+            vec![(TextIndex::LineChar(1, 4), TextIndex::LineChar(2, 0))],
+            parse_ranges("1.4 2.0 3.1")
+        );
+    }
+}
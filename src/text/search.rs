@@ -0,0 +1,166 @@
+//! Text search - options for searching the contents of a text widget.
+//!
+//! Searches are set up using [SearchOptions] in a builder style, and
+//! return the position of each match as a [TextMatch]:
+//!
+//! ```ignore
+//! let matches = text.search_with_options(
+//!     "fn [a-z_]+",
+//!     (1, 0),
+//!     SearchOptions::default().regexp(true).all(true),
+//! )?;
+//! for found in matches {
+//!     println!("{}", text.get(found.start, found.end));
+//! }
+//! ```
+//!
+//! An invalid regular expression is reported as an error, rather than as
+//! finding no matches.
+//!
+//! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/text.htm#M120)
+
+use super::index::{self, TextIndex};
+
+/// Refers to the settings for a text search.
+///
+/// The methods on this struct set the values of different options in a
+/// builder style: pass the result to
+/// [search_with_options](crate::TkText::search_with_options).
+///
+#[derive(Clone, Debug, Default)]
+pub struct SearchOptions {
+    all: bool,
+    backwards: bool,
+    elide: bool,
+    nocase: bool,
+    overlap: bool,
+    regexp: bool,
+    stop_index: Option<TextIndex>,
+}
+
+/// A range of text found by a search: `start` is the first character of
+/// the match, and `end` the position just after its last character.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextMatch {
+    pub start: TextIndex,
+    pub end: TextIndex,
+}
+
+impl SearchOptions {
+    /// Finds every match, rather than only the first.
+    pub fn all(&mut self, value: bool) -> &mut Self {
+        self.all = value;
+        self
+    }
+
+    /// Searches backwards, finding matches ending before the start position.
+    pub fn backwards(&mut self, value: bool) -> &mut Self {
+        self.backwards = value;
+        self
+    }
+
+    /// Searches elided (hidden) text as well as displayed text.
+    pub fn elide(&mut self, value: bool) -> &mut Self {
+        self.elide = value;
+        self
+    }
+
+    /// Ignores differences in case between the pattern and the text.
+    pub fn nocase(&mut self, value: bool) -> &mut Self {
+        self.nocase = value;
+        self
+    }
+
+    /// When finding every match, allows matches to overlap each other.
+    pub fn overlap(&mut self, value: bool) -> &mut Self {
+        self.overlap = value;
+        self
+    }
+
+    /// Treats the pattern as a Tcl regular expression, rather than as
+    /// text to match exactly.
+    pub fn regexp(&mut self, value: bool) -> &mut Self {
+        self.regexp = value;
+        self
+    }
+
+    /// Stops the search at the given position, rather than wrapping around
+    /// the ends of the text.
+    pub fn stop_index(&mut self, index: impl Into<TextIndex>) -> &mut Self {
+        self.stop_index = Some(index.into());
+        self
+    }
+
+    // Returns the switches for the search command, ending with "--".
+    pub(super) fn switches(&self) -> String {
+        let mut switches = String::from(if self.backwards {
+            "-backwards"
+        } else {
+            "-forwards"
+        });
+        switches.push_str(if self.regexp { " -regexp" } else { " -exact" });
+        if self.nocase {
+            switches.push_str(" -nocase");
+        }
+        if self.all {
+            switches.push_str(" -all");
+            if self.overlap {
+                switches.push_str(" -overlap");
+            }
+        }
+        if self.elide {
+            switches.push_str(" -elide");
+        }
+        switches.push_str(" --");
+
+        switches
+    }
+
+    // Returns the stop index, braced, if one is set.
+    pub(super) fn stop(&self) -> String {
+        match &self.stop_index {
+            Some(index) => format!("{{{}}}", index),
+            None => String::new(),
+        }
+    }
+}
+
+// Parses a list of start and end positions into matches.
+pub(super) fn parse_matches(text: &str) -> Vec<TextMatch> {
+    index::parse_ranges(text)
+        .into_iter()
+        .map(|(start, end)| TextMatch { start, end })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_switches() {
+        assert_eq!("-forwards -exact --", SearchOptions::default().switches());
+        assert_eq!(
+            "-backwards -regexp -nocase -all -overlap --",
+            SearchOptions::default()
+                .backwards(true)
+                .regexp(true)
+                .nocase(true)
+                .all(true)
+                .overlap(true)
+                .switches()
+        );
+    }
+
+    #[test]
+    fn search_matches() {
+        assert!(parse_matches("").is_empty());
+
+        let result = parse_matches("1.4 1.9 12.0 12.3");
+        assert_eq!(2, result.len());
+        assert_eq!(TextIndex::LineChar(1, 4), result[0].start);
+        assert_eq!(TextIndex::LineChar(1, 9), result[0].end);
+        assert_eq!(TextIndex::LineChar(12, 0), result[1].start);
+        assert_eq!(TextIndex::LineChar(12, 3), result[1].end);
+    }
+}
@@ -0,0 +1,217 @@
+//! Text tag styles - formatting applied to tagged text.
+//!
+//! ```ignore
+//! text.tag_style(
+//!     "heading",
+//!     TextTagStyle::default()
+//!         .font("Helvetica 14 bold")
+//!         .foreground("navy")
+//!         .spacing3(6),
+//! );
+//! ```
+//!
+//! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/text.htm#M43)
+
+use std::fmt;
+
+use crate::widget;
+
+/// How tab stops are placed in tagged text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TabStyle {
+    /// tabs line up in columns, with a tab skipping a stop already passed
+    Tabular,
+    /// tabs move to the next stop after the current position
+    WordProcessor,
+}
+
+impl fmt::Display for TabStyle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = match self {
+            TabStyle::Tabular => "tabular",
+            TabStyle::WordProcessor => "wordprocessor",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
+/// Refers to the formatting of a text tag.
+///
+/// The methods on this struct set the values of different options in a
+/// builder style: pass the result to [tag_style](crate::TkText::tag_style),
+/// which sets all the options at once. Options not set are left unchanged.
+///
+#[derive(Clone, Debug, Default)]
+pub struct TextTagStyle {
+    options: widget::OptionList,
+}
+
+impl TextTagStyle {
+    /// Specifies the background colour.
+    pub fn background(&mut self, colour: &str) -> &mut Self {
+        self.set("background", colour)
+    }
+
+    /// Specifies a bitmap used as a stipple pattern for the background.
+    pub fn background_stipple(&mut self, bitmap: &str) -> &mut Self {
+        self.set("bgstipple", bitmap)
+    }
+
+    /// Size of border around the background, used with relief.
+    pub fn border_width(&mut self, width: u64) -> &mut Self {
+        self.set("borderwidth", &width.to_string())
+    }
+
+    /// Hides the tagged text when true.
+    pub fn elide(&mut self, value: bool) -> &mut Self {
+        self.set("elide", if value { "1" } else { "0" })
+    }
+
+    /// Specifies the font to use for text.
+    pub fn font(&mut self, definition: &str) -> &mut Self {
+        self.set("font", definition)
+    }
+
+    /// Specifies the foreground (text) colour.
+    pub fn foreground(&mut self, colour: &str) -> &mut Self {
+        self.set("foreground", colour)
+    }
+
+    /// Specifies a bitmap used as a stipple pattern for the text.
+    pub fn foreground_stipple(&mut self, bitmap: &str) -> &mut Self {
+        self.set("fgstipple", bitmap)
+    }
+
+    /// Alignment of lines, applied using the tag on a line's first character.
+    pub fn justify(&mut self, value: widget::Justify) -> &mut Self {
+        self.set("justify", &value.to_string())
+    }
+
+    /// Left indent of the first display line of each line, in pixels.
+    pub fn lmargin1(&mut self, value: u64) -> &mut Self {
+        self.set("lmargin1", &value.to_string())
+    }
+
+    /// Left indent of the other display lines of each line, when wrapped.
+    pub fn lmargin2(&mut self, value: u64) -> &mut Self {
+        self.set("lmargin2", &value.to_string())
+    }
+
+    /// Colour of the left margin.
+    pub fn lmargin_colour(&mut self, colour: &str) -> &mut Self {
+        self.set("lmargincolor", colour)
+    }
+
+    /// Vertical offset of the text from the baseline, in pixels: positive
+    /// for superscripts, negative for subscripts.
+    pub fn offset(&mut self, value: i64) -> &mut Self {
+        self.set("offset", &value.to_string())
+    }
+
+    /// Draws a line through the text when true.
+    pub fn overstrike(&mut self, value: bool) -> &mut Self {
+        self.set("overstrike", if value { "1" } else { "0" })
+    }
+
+    /// Colour of the line drawn through the text.
+    pub fn overstrike_colour(&mut self, colour: &str) -> &mut Self {
+        self.set("overstrikefg", colour)
+    }
+
+    /// Style of border around the background.
+    pub fn relief(&mut self, value: widget::Relief) -> &mut Self {
+        self.set("relief", &value.to_string())
+    }
+
+    /// Right indent of lines, in pixels.
+    pub fn rmargin(&mut self, value: u64) -> &mut Self {
+        self.set("rmargin", &value.to_string())
+    }
+
+    /// Colour of the right margin.
+    pub fn rmargin_colour(&mut self, colour: &str) -> &mut Self {
+        self.set("rmargincolor", colour)
+    }
+
+    /// Background colour of the tagged text when selected.
+    pub fn select_background(&mut self, colour: &str) -> &mut Self {
+        self.set("selectbackground", colour)
+    }
+
+    /// Foreground colour of the tagged text when selected.
+    pub fn select_foreground(&mut self, colour: &str) -> &mut Self {
+        self.set("selectforeground", colour)
+    }
+
+    /// Space above each line, in pixels.
+    pub fn spacing1(&mut self, value: u64) -> &mut Self {
+        self.set("spacing1", &value.to_string())
+    }
+
+    /// Space between the display lines of a wrapped line, in pixels.
+    pub fn spacing2(&mut self, value: u64) -> &mut Self {
+        self.set("spacing2", &value.to_string())
+    }
+
+    /// Space below each line, in pixels.
+    pub fn spacing3(&mut self, value: u64) -> &mut Self {
+        self.set("spacing3", &value.to_string())
+    }
+
+    /// How tab stops are used.
+    pub fn tab_style(&mut self, value: TabStyle) -> &mut Self {
+        self.set("tabstyle", &value.to_string())
+    }
+
+    /// Tab stops, as a list of positions each optionally followed by its
+    /// alignment, e.g. `&["2c", "4c center", "6c numeric"]`.
+    pub fn tabs(&mut self, stops: &[&str]) -> &mut Self {
+        self.set("tabs", &stops.join(" "))
+    }
+
+    /// Underlines the text when true.
+    pub fn underline(&mut self, value: bool) -> &mut Self {
+        self.set("underline", if value { "1" } else { "0" })
+    }
+
+    /// Colour of the underline.
+    pub fn underline_colour(&mut self, colour: &str) -> &mut Self {
+        self.set("underlinefg", colour)
+    }
+
+    /// How wrapping should be performed of long lines.
+    pub fn wrap(&mut self, value: widget::Wrapping) -> &mut Self {
+        self.set("wrap", &value.to_string())
+    }
+
+    // Sets option to value, replacing any value already set.
+    fn set(&mut self, option: &'static str, value: &str) -> &mut Self {
+        self.options.set(option, value);
+        self
+    }
+
+    // Returns the options set, in form "-option value ...".
+    pub(crate) fn options_string(&self) -> String {
+        self.options.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_style_options() {
+        assert_eq!("", TextTagStyle::default().options_string());
+        assert_eq!(
+            "-font Courier\\ 10 -underline 1 -tabs 2c\\ 4c\\ center -foreground blue",
+            TextTagStyle::default()
+                .foreground("red")
+                .font("Courier 10")
+                .underline(true)
+                .tabs(&["2c", "4c center"])
+                .foreground("blue")
+                .options_string()
+        );
+    }
+}
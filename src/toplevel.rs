@@ -1,11 +1,249 @@
 //! Toplevel widget - defines top-level and popup windows.
 //!
 //! * also see the Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/toplevel.htm)
+//!
+//! # Window manager
+//!
+//! Methods on [TkTopLevel] also cover the window manager's settings for the
+//! window, such as its title, geometry and state: see the Tk
+//! [wm manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/wm.htm). Settings
+//! which not every platform supports, such as transparency, and queries,
+//! return a [TkError](wish::TkError) if the window manager reports a
+//! problem.
+//!
+//! # Modal dialogs
+//!
+//! A top-level window can be run as a modal dialog, blocking input to the
+//! other windows until it closes with a value:
+//!
+//! ```ignore
+//! let dialog = rish::make_toplevel(&root);
+//! let name = rish::make_entry(&dialog);
+//! let ok = rish::make_button(&dialog);
+//! ok.command({
+//!     let dialog = dialog.clone();
+//!     let name = name.clone();
+//!     move || dialog.end_modal(name.value_get())
+//! });
+//! // -- layout the dialog
+//!
+//! // -- returns None if the user closed the window instead
+//! let result: Option<String> = dialog.run_modal(&root);
+//! ```
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::str;
+use std::sync::{Mutex, OnceLock};
 
+use super::image;
 use super::menu;
 use super::widget;
+use super::widget::TkWidget;
 use super::wish;
 
+/// Size and position of a top-level window, as used by the window manager.
+///
+/// Displays, and parses, in the Tk form "WIDTHxHEIGHT+X+Y", where each
+/// offset starts with "+" or "-" for the screen edge it is measured from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WindowGeometry {
+    /// width of the window, in pixels
+    pub width: u64,
+    /// height of the window, in pixels
+    pub height: u64,
+    /// horizontal position: from the left or right edge of the screen
+    pub x: WindowOffset,
+    /// vertical position: from the top or bottom edge of the screen
+    pub y: WindowOffset,
+}
+
+#[cfg(test)]
+impl WindowGeometry {
+    /// Geometry from its size and offsets, for building test fixtures.
+    pub(crate) fn new(width: u64, height: u64, x: WindowOffset, y: WindowOffset) -> WindowGeometry {
+        WindowGeometry {
+            width,
+            height,
+            x,
+            y,
+        }
+    }
+}
+
+impl fmt::Display for WindowGeometry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}{}{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+impl str::FromStr for WindowGeometry {
+    type Err = wish::TkError;
+
+    /// Parse from the "WIDTHxHEIGHT+X+Y" form returned by Tk.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || wish::TkError::new(&format!("Invalid geometry: {}", s));
+        let text = s.trim();
+        let (size, position) = text.split_at(text.find(['+', '-']).ok_or_else(invalid)?);
+        let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+        let (x, rest) = WindowOffset::parse_prefix(position).ok_or_else(invalid)?;
+        let (y, rest) = WindowOffset::parse_prefix(rest).ok_or_else(invalid)?;
+        if !rest.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(WindowGeometry {
+            width: width.parse::<u64>().map_err(|_| invalid())?,
+            height: height.parse::<u64>().map_err(|_| invalid())?,
+            x,
+            y,
+        })
+    }
+}
+
+/// Position of a window along one axis of the screen, as the distance of
+/// one of its edges from the same edge of the screen, in pixels. The
+/// distance is negative when the window extends past that screen edge.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowOffset {
+    /// distance of the window's left (or top) edge from the left (or top)
+    /// edge of the screen
+    FromStart(i64),
+    /// distance of the window's right (or bottom) edge from the right (or
+    /// bottom) edge of the screen
+    FromEnd(i64),
+}
+
+impl WindowOffset {
+    // Reads an offset from the start of text - "+" or "-" for the edge,
+    // then the distance, which may itself be negative - returning the
+    // offset and the rest of the text.
+    fn parse_prefix(text: &str) -> Option<(WindowOffset, &str)> {
+        let rest = text.get(1..)?;
+        let length = rest
+            .char_indices()
+            .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+            .map_or(rest.len(), |(i, _)| i);
+        let value = rest[..length].parse::<i64>().ok()?;
+        let offset = match text.chars().next()? {
+            '+' => WindowOffset::FromStart(value),
+            '-' => WindowOffset::FromEnd(value),
+            _ => return None,
+        };
+        Some((offset, &rest[length..]))
+    }
+}
+
+impl fmt::Display for WindowOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowOffset::FromStart(value) => write!(f, "+{}", value),
+            WindowOffset::FromEnd(value) => write!(f, "-{}", value),
+        }
+    }
+}
+
+/// Who asked for a window's position: window managers may only respect a
+/// position given by the user.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PositionSource {
+    Program,
+    User,
+}
+
+impl fmt::Display for PositionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            PositionSource::Program => "program",
+            PositionSource::User => "user",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
+/// State of a top-level window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowState {
+    /// shown as an icon
+    Iconic,
+    /// shown as an icon window for another window
+    Icon,
+    Normal,
+    /// not shown, or managed, by the window manager
+    Withdrawn,
+    /// maximised (Windows and macOS only)
+    Zoomed,
+}
+
+impl WindowState {
+    fn from_name(name: &str) -> Option<WindowState> {
+        match name {
+            "iconic" => Some(WindowState::Iconic),
+            "icon" => Some(WindowState::Icon),
+            "normal" => Some(WindowState::Normal),
+            "withdrawn" => Some(WindowState::Withdrawn),
+            "zoomed" => Some(WindowState::Zoomed),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WindowState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            WindowState::Iconic => "iconic",
+            WindowState::Icon => "icon",
+            WindowState::Normal => "normal",
+            WindowState::Withdrawn => "withdrawn",
+            WindowState::Zoomed => "zoomed",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
+/// Kinds of window, used by X11 window managers to choose how to show a
+/// window.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WindowType {
+    Combo,
+    Desktop,
+    Dialog,
+    Dnd,
+    Dock,
+    DropdownMenu,
+    Menu,
+    Normal,
+    Notification,
+    PopupMenu,
+    Splash,
+    Toolbar,
+    Tooltip,
+    Utility,
+}
+
+impl fmt::Display for WindowType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            WindowType::Combo => "combo",
+            WindowType::Desktop => "desktop",
+            WindowType::Dialog => "dialog",
+            WindowType::Dnd => "dnd",
+            WindowType::Dock => "dock",
+            WindowType::DropdownMenu => "dropdown_menu",
+            WindowType::Menu => "menu",
+            WindowType::Normal => "normal",
+            WindowType::Notification => "notification",
+            WindowType::PopupMenu => "popup_menu",
+            WindowType::Splash => "splash",
+            WindowType::Toolbar => "toolbar",
+            WindowType::Tooltip => "tooltip",
+            WindowType::Utility => "utility",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
 /// Refers to a top-level widget (window)
 #[derive(Clone, Debug, PartialEq)]
 pub struct TkTopLevel {
@@ -29,6 +267,36 @@ impl widget::TkWidget for TkTopLevel {
 }
 
 impl TkTopLevel {
+    /// Sets the opacity of the window, from 0.0 (transparent) to 1.0
+    /// (opaque).
+    pub fn alpha(&self, value: f64) -> Result<(), wish::TkError> {
+        self.wm_attribute("alpha", &value.to_string())
+    }
+
+    /// Returns the opacity of the window.
+    pub fn alpha_get(&self) -> Result<f64, wish::TkError> {
+        let result = wish::eval_wish(&format!("wm attributes {} -alpha", self.id))?;
+        result
+            .parse::<f64>()
+            .map_err(|_| wish::TkError::new(&format!("Invalid alpha value: {}", result)))
+    }
+
+    /// Constrains the window's width/height ratio to lie between
+    /// min_numer/min_denom and max_numer/max_denom.
+    pub fn aspect(&self, min_numer: u64, min_denom: u64, max_numer: u64, max_denom: u64) {
+        let msg = format!(
+            "wm aspect {} {} {} {} {}",
+            self.id, min_numer, min_denom, max_numer, max_denom
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Removes any constraint on the window's width/height ratio.
+    pub fn aspect_clear(&self) {
+        let msg = format!("wm aspect {} {{}} {{}} {{}} {{}}", self.id);
+        wish::tell_wish(&msg);
+    }
+
     /// Specifies the background colour.
     ///
     /// Colours are specified as a string, by either:
@@ -50,18 +318,34 @@ impl TkTopLevel {
         wish::tell_wish(&msg);
     }
 
+    /// Closes the window, when run as a modal dialog, making
+    /// [run_modal](TkTopLevel::run_modal) return the given value.
+    pub fn end_modal<T: Send + 'static>(&self, value: T) {
+        modal_results()
+            .lock()
+            .unwrap()
+            .insert(self.id.clone(), Box::new(value));
+        self.destroy();
+    }
+
+    /// Returns the id of the window manager's frame around this window,
+    /// or of the window itself if it has no frame.
+    pub fn frame_id(&self) -> Result<String, wish::TkError> {
+        wish::eval_wish(&format!("wm frame {}", self.id))
+    }
+
     /// Expand window to occupy the full screen.
     pub fn full_screen(&self) {
         let msg = format!("wm attributes {} -fullscreen 1", self.id);
         wish::tell_wish(&msg);
     }
 
-    /// Retrieves the geometry of the window as a tuple: (width, height, x, y).
-    pub fn geometry_get(&self) -> (u64, u64, u64, u64) {
-        let msg = format!("puts [wm geometry {}] ; flush stdout", self.id);
-        let result = wish::ask_wish(&msg);
-
-        string_geometry(&result)
+    /// Retrieves the size and position of the window: the position keeps
+    /// the screen edges it is measured from, so it can be given back to
+    /// [set_geometry](TkTopLevel::set_geometry).
+    pub fn geometry_get(&self) -> Result<WindowGeometry, wish::TkError> {
+        let result = wish::eval_wish(&format!("wm geometry {}", self.id))?;
+        result.parse::<WindowGeometry>()
     }
 
     /// Sets the size and position of a top-level window.
@@ -86,11 +370,30 @@ impl TkTopLevel {
         wish::tell_wish(&msg);
     }
 
+    /// Makes this window part of a group led by given window: window
+    /// managers may, e.g., iconify the group together.
+    pub fn group(&self, leader: &impl widget::TkWidget) {
+        let msg = format!("wm group {} {}", self.id, leader.id());
+        wish::tell_wish(&msg);
+    }
+
     /// Height of window, in rows.
     pub fn height(&self, height: u64) {
         widget::configure(&self.id, "height", &height.to_string());
     }
 
+    /// Sets the image used as the window's icon: if `default` is true, it
+    /// is also used for top-level windows created later.
+    pub fn icon_photo(&self, image: &image::TkImage, default: bool) {
+        let msg = format!(
+            "wm iconphoto {} {} {}",
+            self.id,
+            if default { "-default" } else { "" },
+            image.id
+        );
+        wish::tell_wish(&msg);
+    }
+
     /// Iconify the window.
     pub fn iconify(&self) {
         let msg = format!("wm iconify {}", self.id);
@@ -124,6 +427,19 @@ impl TkTopLevel {
         wish::tell_wish(&msg);
     }
 
+    /// When true, the window manager ignores this window: it has no
+    /// decorations and cannot be moved or closed by the user. Used for,
+    /// e.g., tooltips and splash screens. Takes effect when the window is
+    /// next shown.
+    pub fn override_redirect(&self, value: bool) {
+        let msg = format!(
+            "wm overrideredirect {} {}",
+            self.id,
+            if value { "1" } else { "0" }
+        );
+        wish::tell_wish(&msg);
+    }
+
     /// Amount of horizontal padding for widget.
     pub fn padx(&self, value: u64) {
         widget::configure(&self.id, "padx", &value.to_string());
@@ -134,6 +450,12 @@ impl TkTopLevel {
         widget::configure(&self.id, "pady", &value.to_string());
     }
 
+    /// Sets who asked for the window's position.
+    pub fn position_from(&self, value: PositionSource) {
+        let msg = format!("wm positionfrom {} {}", self.id, value);
+        wish::tell_wish(&msg);
+    }
+
     /// Style of border around label.
     pub fn relief(&self, value: widget::Relief) {
         widget::configure(&self.id, "relief", &value.to_string());
@@ -150,12 +472,86 @@ impl TkTopLevel {
         wish::tell_wish(&msg);
     }
 
+    /// Sets the size and position of the window, e.g. as returned by
+    /// [geometry_get](TkTopLevel::geometry_get).
+    pub fn set_geometry(&self, geometry: &WindowGeometry) {
+        let msg = format!("wm geometry {} {}", self.id, geometry);
+        wish::tell_wish(&msg);
+    }
+
+    /// Shows the window as a modal dialog over given parent, and waits,
+    /// processing events, until the window is closed. Input to other
+    /// windows is blocked meanwhile, and the focus is restored afterwards.
+    ///
+    /// Returns the value given to [end_modal](TkTopLevel::end_modal), or
+    /// None if the window was closed otherwise, or the value is not of type
+    /// T.
+    pub fn run_modal<T: Send + 'static>(&self, parent: &impl widget::TkWidget) -> Option<T> {
+        let focus = wish::ask_wish("puts [focus] ; flush stdout");
+        modal_results().lock().unwrap().remove(&self.id);
+
+        let msg = format!(
+            "wm transient {id} [winfo toplevel {}] ; wm deiconify {id} ; rish_modal_grab {id}",
+            parent.id(),
+            id = self.id
+        );
+        wish::tell_wish(&msg);
+        self.wait_window();
+
+        if !focus.is_empty() {
+            let msg = format!("if {{[winfo exists {0}]}} {{focus {0}}}", focus);
+            wish::tell_wish(&msg);
+        }
+
+        let value = modal_results().lock().unwrap().remove(&self.id)?;
+        value.downcast::<T>().ok().map(|value| *value)
+    }
+
+    /// Returns the top-level windows, from this window downwards, in their
+    /// stacking order from lowest to highest.
+    pub fn stack_order(&self) -> Result<Vec<String>, wish::TkError> {
+        let result = wish::eval_wish(&format!("wm stackorder {}", self.id))?;
+        Ok(wish::split_list(&result))
+    }
+
+    /// Sets the state of the window, e.g. to iconify or withdraw it.
+    pub fn state(&self, value: WindowState) -> Result<(), wish::TkError> {
+        wish::eval_wish(&format!("wm state {} {}", self.id, value))?;
+        Ok(())
+    }
+
+    /// Returns the state of the window.
+    pub fn state_get(&self) -> Result<WindowState, wish::TkError> {
+        let result = wish::eval_wish(&format!("wm state {}", self.id))?;
+        WindowState::from_name(&result)
+            .ok_or_else(|| wish::TkError::new(&format!("Unknown window state: {}", result)))
+    }
+
     /// Sets the title text on a top-level window.
     pub fn title(&self, title: &str) {
         let msg = format!("wm title {} {{{}}}\n", self.id, title);
         wish::tell_wish(&msg);
     }
 
+    /// Keeps the window above all other windows when true.
+    pub fn topmost(&self, value: bool) -> Result<(), wish::TkError> {
+        self.wm_attribute("topmost", if value { "1" } else { "0" })
+    }
+
+    /// Marks the window as working for given window, e.g. as its dialog:
+    /// window managers keep it above that window, and may not show it
+    /// separately.
+    pub fn transient(&self, master: &impl widget::TkWidget) {
+        let msg = format!("wm transient {} {}", self.id, master.id());
+        wish::tell_wish(&msg);
+    }
+
+    /// Stops the window being [transient](TkTopLevel::transient).
+    pub fn transient_clear(&self) {
+        let msg = format!("wm transient {} {{}}", self.id);
+        wish::tell_wish(&msg);
+    }
+
     /// Updates the display.
     pub fn update_idle_tasks(&self) {
         wish::tell_wish("update idletasks");
@@ -166,43 +562,77 @@ impl TkTopLevel {
         widget::configure(&self.id, "width", &width.to_string());
     }
 
+    /// Sets the kind of window, used by X11 window managers.
+    pub fn window_type(&self, value: WindowType) -> Result<(), wish::TkError> {
+        self.wm_attribute("type", &value.to_string())
+    }
+
     /// Withdraw the window.
     pub fn withdraw(&self) {
         let msg = format!("wm withdraw {}", self.id);
         wish::tell_wish(&msg);
     }
-}
 
-// Parse the widthxheight+x+y tcl string into a tuple: (width, height, x, y).
-// -- we will ignore errors because this is only called on the return value
-// from a tcl call.
-// -- return (0,0,0,0) if there is a problem
-fn string_geometry(text: &str) -> (u64, u64, u64, u64) {
-    let parts: Vec<&str> = text.split(&['x', '+'][..]).collect();
-    let mut w = 0;
-    let mut h = 0;
-    let mut x = 0;
-    let mut y = 0;
-    if parts.len() == 4 {
-        w = parts[0].parse::<u64>().unwrap_or(0);
-        h = parts[1].parse::<u64>().unwrap_or(0);
-        x = parts[2].parse::<u64>().unwrap_or(0);
-        y = parts[3].parse::<u64>().unwrap_or(0);
+    fn wm_attribute(&self, attribute: &str, value: &str) -> Result<(), wish::TkError> {
+        let script = format!("wm attributes {} -{} {}", self.id, attribute, value);
+        wish::eval_wish(&script)?;
+        Ok(())
     }
-    (w, h, x, y)
+}
+
+// -- Store for the values given to end_modal, by window id
+
+fn modal_results() -> &'static Mutex<HashMap<String, Box<dyn Any + Send>>> {
+    static MODAL_RESULTS: OnceLock<Mutex<HashMap<String, Box<dyn Any + Send>>>> = OnceLock::new();
+
+    MODAL_RESULTS.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::string_geometry;
+    use super::*;
+
+    #[test]
+    fn window_state_names() {
+        assert_eq!(Some(WindowState::Zoomed), WindowState::from_name("zoomed"));
+        assert_eq!(None, WindowState::from_name("maximised"));
+    }
+
+    #[test]
+    fn geometry_negative() {
+        use WindowOffset::*;
+
+        // -- past the left edge, and from the right and bottom edges
+        let past_left = WindowGeometry::new(300, 200, FromStart(-5), FromStart(10));
+        let from_end = WindowGeometry::new(300, 200, FromEnd(20), FromEnd(30));
+        assert_eq!(past_left, "300x200+-5+10".parse().unwrap());
+        assert_eq!(from_end, "300x200-20-30".parse().unwrap());
+        assert_eq!(
+            WindowGeometry::new(300, 200, FromEnd(-5), FromStart(0)),
+            "300x200--5+0".parse().unwrap()
+        );
+        // -- round trip through the form given to Tk
+        assert_eq!("300x200+-5+10", past_left.to_string());
+        assert_eq!("300x200-20-30", from_end.to_string());
+        assert!("300x200+5".parse::<WindowGeometry>().is_err());
+    }
 
     #[test]
     fn geometry() {
-        assert_eq!((0, 0, 0, 0), string_geometry("0x0+0+0"));
-        assert_eq!((10, 20, 100, 200), string_geometry("10x20+100+200"));
+        use WindowOffset::*;
+
+        assert_eq!(
+            WindowGeometry::new(0, 0, FromStart(0), FromStart(0)),
+            "0x0+0+0".parse().unwrap()
+        );
+        assert_eq!(
+            WindowGeometry::new(10, 20, FromStart(100), FromStart(200)),
+            "10x20+100+200".parse().unwrap()
+        );
         // - check some unlikely errors
-        assert_eq!((0, 0, 0, 0), string_geometry("0x00+0"));
-        assert_eq!((0, 0, 0, 0), string_geometry(""));
-        assert_eq!((0, 0, 0, 0), string_geometry("axbxcxd"));
+        assert!("0x00+0".parse::<WindowGeometry>().is_err());
+        assert!("".parse::<WindowGeometry>().is_err());
+        assert!("axbxcxd".parse::<WindowGeometry>().is_err());
+        assert!("10x20+1+2+3".parse::<WindowGeometry>().is_err());
     }
 }
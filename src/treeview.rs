@@ -16,15 +16,25 @@
 //!
 //! # Events
 //!
-//! Use [bind](widget::TkWidget::bind) to call a function on following event:
+//! Use [bind](widget::TkWidget::bind) to call a function on following event,
+//! or the given method to be passed the items concerned:
 //!
-//! * `<<TreeviewSelect>>` - whenever selection is changed
-//! * `<<TreeviewOpen>>` - when a node is opened
-//! * `<<TreeviewClose>>` - when a node is closed
+//! * `<<TreeviewSelect>>` - whenever selection is changed ([on_select](TkTreeview::on_select))
+//! * `<<TreeviewOpen>>` - when a node is opened ([on_open](TkTreeview::on_open))
+//! * `<<TreeviewClose>>` - when a node is closed ([on_close](TkTreeview::on_close))
+//!
+//! # Items
+//!
+//! Items are referred to by an id string, allocated by Tk. A
+//! [TkTreeviewItem] can be re-created from a stored id using
+//! [item](TkTreeview::item).
+
+use std::fmt;
 
 use super::grid;
 use super::image;
 use super::pack;
+use super::place;
 use super::widget;
 use super::wish;
 
@@ -41,6 +51,42 @@ pub struct TkTreeviewItem {
     pub id: String,
 }
 
+/// Part of the treeview at a position, as returned by
+/// [identify_region](TkTreeview::identify_region).
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeviewRegion {
+    Cell,
+    Heading,
+    Nothing,
+    Separator,
+    Tree,
+}
+
+impl fmt::Display for TreeviewRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            TreeviewRegion::Cell => "cell",
+            TreeviewRegion::Heading => "heading",
+            TreeviewRegion::Nothing => "nothing",
+            TreeviewRegion::Separator => "separator",
+            TreeviewRegion::Tree => "tree",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
+impl TreeviewRegion {
+    fn from_name(name: &str) -> TreeviewRegion {
+        match name.trim() {
+            "cell" => TreeviewRegion::Cell,
+            "heading" => TreeviewRegion::Heading,
+            "separator" => TreeviewRegion::Separator,
+            "tree" => TreeviewRegion::Tree,
+            _ => TreeviewRegion::Nothing,
+        }
+    }
+}
+
 /// Creates an instance of a treeview widget in given parent.
 pub fn make_treeview(parent: &impl widget::TkWidget) -> TkTreeview {
     let id = wish::next_wid(parent.id());
@@ -59,6 +105,7 @@ impl widget::TkWidget for TkTreeview {
 
 impl grid::TkGridLayout for TkTreeview {}
 impl pack::TkPackLayout for TkTreeview {}
+impl place::TkPlaceLayout for TkTreeview {}
 
 impl TkTreeview {
     /// Defines the names for the columns, used when referring to
@@ -105,6 +152,23 @@ impl TkTreeview {
         wish::tell_wish(&msg);
     }
 
+    /// Returns the width in pixels of each column, including the tree
+    /// column "#0", as (column, width) pairs.
+    pub fn column_widths(&self) -> Vec<(String, u64)> {
+        let script = format!(
+            "lmap c [concat #0 [{0} cget -columns]] {{ list $c [{0} column $c -width] }}",
+            &self.id
+        );
+        let result = wish::eval_wish(&script).unwrap_or_default();
+        wish::split_list(&result)
+            .iter()
+            .filter_map(|pair| match wish::split_list(pair).as_slice() {
+                [column, width] => Some((column.clone(), width.parse::<u64>().ok()?)),
+                _ => None,
+            })
+            .collect()
+    }
+
     /// Returns the focussed item.
     pub fn focus(&self) -> TkTreeviewItem {
         let msg = format!("puts [{} focus] ; flush stdout ", &self.id);
@@ -116,6 +180,34 @@ impl TkTreeview {
         }
     }
 
+    /// Makes the rows sortable by clicking on a column heading: the
+    /// children of each item are sorted by that column's values, numerically
+    /// if all the values are numbers. Clicking the same heading again
+    /// reverses the order, and the sorted column's heading shows the
+    /// direction with an arrow.
+    ///
+    /// Call this after setting the [columns](TkTreeview::columns): it
+    /// replaces any [heading_command](TkTreeview::heading_command).
+    pub fn enable_sorting(&self) {
+        let msg = format!(
+            "foreach c [concat #0 [{0} cget -columns]] {{ {0} heading $c -command [list rish_treeview_sort {0} $c] }}",
+            &self.id
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Sets the function to be called when the given column's heading is
+    /// clicked.
+    pub fn heading_command(&self, column: &str, command: impl Fn() + Send + 'static) {
+        let key = wish::callback_key(&self.id, &format!("heading{}", column));
+        wish::add_callback0(&key, wish::mk_callback0(command));
+        let msg = format!(
+            "{} heading {} -command {{ puts clicked-{} ; flush stdout }}",
+            &self.id, column, key
+        );
+        wish::tell_wish(&msg);
+    }
+
     /// Set the heading text for the given column.
     pub fn heading_text(&self, column: &str, title: &str) {
         let msg = format!("{} heading {} -text {{{}}}", &self.id, column, title);
@@ -139,6 +231,38 @@ impl TkTreeview {
         widget::configure(&self.id, "height", &value.to_string());
     }
 
+    /// Returns the column at the given x position, in form "#n", where
+    /// "#0" is the tree column.
+    pub fn identify_column(&self, x: i64) -> String {
+        let msg = format!("puts [{} identify column {} 0] ; flush stdout", &self.id, x);
+        wish::ask_wish(&msg)
+    }
+
+    /// Returns the item at the given position, if there is one.
+    pub fn identify_item(&self, x: i64, y: i64) -> Option<TkTreeviewItem> {
+        let msg = format!(
+            "puts [{} identify item {} {}] ; flush stdout",
+            &self.id, x, y
+        );
+        let result = wish::ask_wish(&msg);
+
+        if result.is_empty() {
+            None
+        } else {
+            Some(self.item(&result))
+        }
+    }
+
+    /// Returns the part of the treeview at the given position.
+    pub fn identify_region(&self, x: i64, y: i64) -> TreeviewRegion {
+        let msg = format!(
+            "puts [{} identify region {} {}] ; flush stdout",
+            &self.id, x, y
+        );
+        let result = wish::ask_wish(&msg);
+        TreeviewRegion::from_name(&result)
+    }
+
     /// Create a new top-level item at end of treeview.
     pub fn insert_item(&self) -> TkTreeviewItem {
         let msg = format!("puts [{} insert {{}} end] ; flush stdout", &self.id);
@@ -161,12 +285,80 @@ impl TkTreeview {
         }
     }
 
+    /// Returns the item with the given id, e.g. one stored from an
+    /// earlier [TkTreeviewItem].
+    pub fn item(&self, id: &str) -> TkTreeviewItem {
+        TkTreeviewItem {
+            treeview: self.id.clone(),
+            id: String::from(id),
+        }
+    }
+
     /// Moves a given item to become a child of given parent.
     pub fn move_item(&self, child: &TkTreeviewItem, parent: &TkTreeviewItem, index: u64) {
-        let msg = format!("{} move {} {} {}", &self.id, &child.id, &parent.id, index);
+        let msg = format!(
+            "{} move {} {} {}",
+            &self.id,
+            child.tcl_id(),
+            parent.tcl_id(),
+            index
+        );
         wish::tell_wish(&msg);
     }
 
+    /// Sets the function to be called when an item is closed, which is
+    /// passed the closed item.
+    pub fn on_close(&self, command: impl Fn(TkTreeviewItem) + Send + 'static) {
+        self.bind_item_event("<<TreeviewClose>>", "close", command);
+    }
+
+    /// Sets the function to be called when an item is opened, which is
+    /// passed the opened item.
+    pub fn on_open(&self, command: impl Fn(TkTreeviewItem) + Send + 'static) {
+        self.bind_item_event("<<TreeviewOpen>>", "open", command);
+    }
+
+    /// Sets the function to be called when the selection changes, which
+    /// is passed the selected items. Other bindings of `<<TreeviewSelect>>`
+    /// are kept.
+    pub fn on_select(&self, command: impl Fn(Vec<TkTreeviewItem>) + Send + 'static) {
+        let treeview = self.clone();
+        let key = wish::callback_key(&self.id, "select");
+        wish::add_callback0(
+            &key,
+            wish::mk_callback0(move || command(treeview.selected_items())),
+        );
+        widget::bind_callback0(&self.id, "<<TreeviewSelect>>", &key);
+    }
+
+    /// Returns the items which are open, at any depth in the tree.
+    pub fn open_items(&self) -> Vec<TkTreeviewItem> {
+        let script = format!(
+            "apply {{{{t}} {{
+                set open {{}}
+                set items [$t children {{}}]
+                while {{[llength $items] > 0}} {{
+                    set items [lassign $items item]
+                    if {{[$t item $item -open]}} {{ lappend open $item }}
+                    lappend items {{*}}[$t children $item]
+                }}
+                return $open
+            }}}} {}",
+            &self.id
+        );
+        let result = wish::eval_wish(&script).unwrap_or_default();
+        wish::split_list(&result)
+            .iter()
+            .map(|id| self.item(id))
+            .collect()
+    }
+
+    /// Returns the invisible root item, the parent of all top-level items:
+    /// used to move items to the top level.
+    pub fn root_item(&self) -> TkTreeviewItem {
+        self.item("")
+    }
+
     /// Selection mode.
     pub fn select_mode(&self, value: widget::Selection) {
         widget::configure(&self.id, "selectmode", &value.to_string());
@@ -188,6 +380,26 @@ impl TkTreeview {
         result
     }
 
+    /// Adds the given items to the selection.
+    pub fn selection_add(&self, items: &[&TkTreeviewItem]) {
+        self.selection_change("add", items);
+    }
+
+    /// Removes the given items from the selection.
+    pub fn selection_remove(&self, items: &[&TkTreeviewItem]) {
+        self.selection_change("remove", items);
+    }
+
+    /// Sets the selection to be just the given items.
+    pub fn selection_set(&self, items: &[&TkTreeviewItem]) {
+        self.selection_change("set", items);
+    }
+
+    /// Toggles whether each of the given items is selected.
+    pub fn selection_toggle(&self, items: &[&TkTreeviewItem]) {
+        self.selection_change("toggle", items);
+    }
+
     /// Shows both the tree and headers (default setting).
     pub fn show_all(&self) {
         widget::configure(&self.id, "show", "tree headings");
@@ -211,7 +423,7 @@ impl TkTreeview {
         command: impl Fn(widget::TkEvent) + Send + 'static,
     ) {
         // id+tag+pattern used as identifier
-        let tag_pattern = format!("{}{}{}", &self.id, tag, pattern);
+        let tag_pattern = wish::callback_key(&self.id, &format!("{}{}", tag, pattern));
         wish::add_callback1_event(&tag_pattern, wish::mk_callback1_event(command));
         let msg = format!(
             "{} tag bind {} {} {{ puts cb1e:{}:%x:%y:%X:%Y:%h:%w:%k:%K:%b ; flush stdout }}",
@@ -235,24 +447,149 @@ impl TkTreeview {
         let result = wish::ask_wish(&msg);
         wish::split_items(&result)
     }
+
+    // Binds an event reported on the focus item, passing that item to
+    // command.
+    fn bind_item_event(
+        &self,
+        pattern: &str,
+        name: &str,
+        command: impl Fn(TkTreeviewItem) + Send + 'static,
+    ) {
+        let treeview = self.clone();
+        let key = wish::callback_key(&self.id, name);
+        wish::add_callback0(&key, wish::mk_callback0(move || command(treeview.focus())));
+        widget::bind_callback0(&self.id, pattern, &key);
+    }
+
+    fn selection_change(&self, operation: &str, items: &[&TkTreeviewItem]) {
+        let ids: Vec<String> = items.iter().map(|item| item.tcl_id()).collect();
+        let msg = format!(
+            "{} selection {} [list {}]",
+            &self.id,
+            operation,
+            ids.join(" ")
+        );
+        wish::tell_wish(&msg);
+    }
 }
 
 impl TkTreeviewItem {
+    // Returns the item's id, quoted for use in a tcl command: the root
+    // item's id is empty.
+    fn tcl_id(&self) -> String {
+        wish::tcl_quote(&self.id)
+    }
+
+    /// Returns the bounding box (x, y, width, height) of this item, if
+    /// it is visible.
+    pub fn bbox(&self) -> Option<(i64, i64, i64, i64)> {
+        self.bbox_of(None)
+    }
+
+    /// Returns the bounding box (x, y, width, height) of the given
+    /// column's cell in this item, if it is visible.
+    pub fn bbox_column(&self, column: &str) -> Option<(i64, i64, i64, i64)> {
+        self.bbox_of(Some(column))
+    }
+
     /// Deletes this widget from tree.
     pub fn delete(&self) {
-        let msg = format!("{} delete {}", &self.treeview, &self.id);
+        let msg = format!("{} delete {}", &self.treeview, self.tcl_id());
         wish::tell_wish(&msg);
     }
 
+    /// Removes this item, and its children, from the tree without deleting
+    /// it: it can be put back using [reattach](TkTreeviewItem::reattach).
+    pub fn detach(&self) {
+        let msg = format!("{} detach {}", &self.treeview, self.tcl_id());
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns true if this item exists in the treeview, including when
+    /// detached.
+    pub fn exists(&self) -> bool {
+        let msg = format!(
+            "puts [{} exists {}] ; flush stdout",
+            &self.treeview,
+            self.tcl_id()
+        );
+        let result = wish::ask_wish(&msg);
+
+        result == "1"
+    }
+
+    /// Returns the position of this item in its parent's list of children.
+    pub fn index(&self) -> u64 {
+        let msg = format!(
+            "puts [{} index {}] ; flush stdout",
+            &self.treeview,
+            self.tcl_id()
+        );
+        let result = wish::ask_wish(&msg);
+        result.parse::<u64>().unwrap_or(0)
+    }
+
+    /// Puts a detached item back in the tree, as a child of parent at given
+    /// index position: use [root_item](TkTreeview::root_item) as the parent
+    /// for a top-level item.
+    pub fn reattach(&self, parent: &TkTreeviewItem, index: u64) {
+        let msg = format!(
+            "{} move {} {} {}",
+            &self.treeview,
+            self.tcl_id(),
+            parent.tcl_id(),
+            index
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Scrolls the treeview, and opens this item's ancestors, so this
+    /// item is visible.
+    pub fn see(&self) {
+        let msg = format!("{} see {}", &self.treeview, self.tcl_id());
+        wish::tell_wish(&msg);
+    }
+
+    fn bbox_of(&self, column: Option<&str>) -> Option<(i64, i64, i64, i64)> {
+        let script = format!(
+            "{} bbox {} {}",
+            &self.treeview,
+            self.tcl_id(),
+            column.map(wish::tcl_quote).unwrap_or_default()
+        );
+        let result = wish::eval_wish(&script).ok()?;
+        let parts: Vec<i64> = result
+            .split_whitespace()
+            .filter_map(|part| part.parse::<i64>().ok())
+            .collect();
+
+        if parts.len() == 4 {
+            Some((parts[0], parts[1], parts[2], parts[3]))
+        } else {
+            None
+        }
+    }
+
     /// Sets the text label for the item.
     pub fn text(&self, value: &str) {
-        let msg = format!("{} item {} -text {{{}}}", &self.treeview, &self.id, value);
+        let msg = format!(
+            "{} item {} -text {{{}}}",
+            &self.treeview,
+            self.tcl_id(),
+            value
+        );
         wish::tell_wish(&msg);
     }
 
     /// Sets an image to display on the item.
     pub fn image(&self, image: &image::TkImage) {
-        let msg = format!("{} item {} -image {}", &self.treeview, &self.id, &image.id);
+        let msg = format!(
+            "{} item {} -image {}",
+            &self.treeview,
+            self.tcl_id(),
+            &image.id
+        );
         wish::tell_wish(&msg);
     }
 
@@ -268,7 +605,44 @@ impl TkTreeviewItem {
 
         let msg = format!(
             "{} item {} -values {{{}}}",
-            &self.treeview, &self.id, values_str
+            &self.treeview,
+            self.tcl_id(),
+            values_str
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns the value displayed in the given column for this item.
+    pub fn value_get(&self, column: &str) -> String {
+        let msg = format!(
+            "puts [{} set {} {}] ; flush stdout",
+            &self.treeview,
+            self.tcl_id(),
+            wish::tcl_quote(column)
+        );
+        wish::ask_wish(&msg)
+    }
+
+    /// Returns the list of values displayed for this item.
+    pub fn values_get(&self) -> Vec<String> {
+        let msg = format!(
+            "puts [{} item {} -values] ; flush stdout",
+            &self.treeview,
+            self.tcl_id()
+        );
+        let result = wish::ask_wish(&msg);
+        wish::split_list(&result)
+    }
+
+    /// Sets the value displayed in the given column for this item, leaving
+    /// the other columns unchanged.
+    pub fn set(&self, column: &str, value: &str) {
+        let msg = format!(
+            "{} set {} {} {}",
+            &self.treeview,
+            self.tcl_id(),
+            column,
+            wish::tcl_quote(value)
         );
         wish::tell_wish(&msg);
     }
@@ -278,7 +652,7 @@ impl TkTreeviewItem {
         let msg = format!(
             "{} item {} -open {}",
             &self.treeview,
-            &self.id,
+            self.tcl_id(),
             if value { "1" } else { "0" }
         );
         wish::tell_wish(&msg);
@@ -288,7 +662,8 @@ impl TkTreeviewItem {
     pub fn is_open(&self) -> bool {
         let msg = format!(
             "puts [{} item {} -open] ; flush stdout",
-            &self.treeview, &self.id
+            &self.treeview,
+            self.tcl_id()
         );
         let result = wish::ask_wish(&msg);
 
@@ -299,7 +674,8 @@ impl TkTreeviewItem {
     pub fn insert_item(&self) -> TkTreeviewItem {
         let msg = format!(
             "puts [{} insert {} end] ; flush stdout ",
-            &self.treeview, &self.id
+            &self.treeview,
+            self.tcl_id()
         );
         let result = wish::ask_wish(&msg);
 
@@ -314,7 +690,9 @@ impl TkTreeviewItem {
     pub fn insert_item_at(&self, index: u64) -> TkTreeviewItem {
         let msg = format!(
             "puts [{} insert {} {}] ; flush stdout",
-            &self.treeview, &self.id, index
+            &self.treeview,
+            self.tcl_id(),
+            index
         );
         let result = wish::ask_wish(&msg);
 
@@ -329,7 +707,8 @@ impl TkTreeviewItem {
     pub fn parent(&self) -> Option<TkTreeviewItem> {
         let msg = format!(
             "puts [{} parent {}] ; flush stdout",
-            &self.treeview, &self.id
+            &self.treeview,
+            self.tcl_id()
         );
         let result = wish::ask_wish(&msg);
 
@@ -347,7 +726,11 @@ impl TkTreeviewItem {
     /// this one in its list, if found, or None if this is the
     /// first child of its parent.
     pub fn previous(&self) -> Option<TkTreeviewItem> {
-        let msg = format!("puts [{} prev {}] ; flush stdout", &self.treeview, &self.id);
+        let msg = format!(
+            "puts [{} prev {}] ; flush stdout",
+            &self.treeview,
+            self.tcl_id()
+        );
         let result = wish::ask_wish(&msg);
 
         if result.is_empty() || result == "{}" {
@@ -364,7 +747,11 @@ impl TkTreeviewItem {
     /// this one in its list, if found, or None if this is the
     /// last child of its parent.
     pub fn next(&self) -> Option<TkTreeviewItem> {
-        let msg = format!("puts [{} next {}] ; flush stdout", &self.treeview, &self.id);
+        let msg = format!(
+            "puts [{} next {}] ; flush stdout",
+            &self.treeview,
+            self.tcl_id()
+        );
         let result = wish::ask_wish(&msg);
 
         if result.is_empty() || result == "{}" {
@@ -381,7 +768,8 @@ impl TkTreeviewItem {
     pub fn children(&self) -> Vec<TkTreeviewItem> {
         let msg = format!(
             "puts [{} children {}] ; flush stdout",
-            &self.treeview, &self.id
+            &self.treeview,
+            self.tcl_id()
         );
         let result = wish::ask_wish(&msg);
 
@@ -399,7 +787,7 @@ impl TkTreeviewItem {
 
     /// Adds a tag to this item.
     pub fn tag_add(&self, tag: &str) {
-        let msg = format!("{} tag add {{{}}} {}", &self.treeview, tag, &self.id);
+        let msg = format!("{} tag add {{{}}} {}", &self.treeview, tag, self.tcl_id());
         wish::tell_wish(&msg);
     }
 
@@ -407,7 +795,9 @@ impl TkTreeviewItem {
     pub fn tag_has(&self, tag: &str) -> bool {
         let msg = format!(
             "puts [{} tag has {{{}}} {}] ; flush stdout",
-            &self.treeview, tag, &self.id
+            &self.treeview,
+            tag,
+            self.tcl_id()
         );
         let result = wish::ask_wish(&msg);
 
@@ -416,7 +806,31 @@ impl TkTreeviewItem {
 
     /// Removes a tag from this item.
     pub fn tag_remove(&self, tag: &str) {
-        let msg = format!("{} tag remove {{{}}} {}", &self.treeview, tag, &self.id);
+        let msg = format!(
+            "{} tag remove {{{}}} {}",
+            &self.treeview,
+            tag,
+            self.tcl_id()
+        );
         wish::tell_wish(&msg);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn treeview_region_names() {
+        for region in [
+            TreeviewRegion::Cell,
+            TreeviewRegion::Heading,
+            TreeviewRegion::Nothing,
+            TreeviewRegion::Separator,
+            TreeviewRegion::Tree,
+        ] {
+            assert_eq!(region, TreeviewRegion::from_name(&region.to_string()));
+        }
+        assert_eq!(TreeviewRegion::Nothing, TreeviewRegion::from_name(""));
+    }
+}
@@ -0,0 +1,188 @@
+//! UI macro - declares a tree of widgets, with their options and layouts,
+//! in one place.
+//!
+//! [ui!](crate::ui!) defines a struct holding a handle to each widget,
+//! with a `new` function which creates the widgets in a given parent:
+//!
+//! ```ignore
+//! rish::ui! {
+//!     pub struct LoginForm {
+//!         name_label: TkLabel { text("Name:") } grid { row(0), column(0), sticky(Sticky::E) },
+//!         name: TkEntry {} grid { row(0), column(1), sticky(Sticky::EW) },
+//!         buttons: TkFrame {} grid { row(1), column(1), sticky(Sticky::E) },
+//!         cancel: TkButton in buttons { text("Cancel") } pack { side(PackSide::Left) },
+//!         ok: TkButton in buttons {
+//!             text("OK"),
+//!             command({
+//!                 let name = name.clone();
+//!                 move || println!("Hello {}", name.value_get())
+//!             }),
+//!         } pack { side(PackSide::Left), padx(5) },
+//!     }
+//! }
+//!
+//! let form = LoginForm::new(&root);
+//! form.name.focus();
+//! ```
+//!
+//! Each field is written as:
+//!
+//! ```text
+//! NAME: TYPE [in PARENT] [= CONSTRUCTOR(ARGS)] { METHOD(ARGS), ... } [LAYOUT { METHOD(ARGS), ... }]
+//! ```
+//!
+//! * `TYPE` is the widget's struct, e.g. `TkLabel`.
+//! * `in PARENT` names an earlier field to create the widget in: without
+//!   it, the widget is created in the parent given to `new`.
+//! * `= CONSTRUCTOR(ARGS)` gives the function creating the widget, for
+//!   widgets whose constructor takes more than the parent, e.g.
+//!   `= make_scale(Orientation::Horizontal)`: the parent is passed first.
+//!   Other widgets are created with their usual `make_WIDGET` function.
+//! * the methods in braces are called on the widget, in order.
+//! * `LAYOUT` is one of `grid`, `pack` or `place`: the methods are called on
+//!   the layout builder, before its `layout`.
+//!
+//! Fields are created in order, and each is available by name to the
+//! arguments of later fields, e.g. to use in a callback.
+//!
+
+use super::button;
+use super::canvas;
+use super::check_button;
+use super::entry;
+use super::frame;
+use super::label;
+use super::label_frame;
+use super::log_view;
+use super::notebook;
+use super::text;
+use super::treeview;
+use super::widget;
+
+/// Widgets created by [ui!](crate::ui!) without a constructor given.
+pub trait MakeWidget {
+    /// Creates an instance of the widget in given parent.
+    fn make(parent: &impl widget::TkWidget) -> Self;
+}
+
+impl MakeWidget for button::TkButton {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        button::make_button(parent)
+    }
+}
+
+impl MakeWidget for canvas::TkCanvas {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        canvas::make_canvas(parent)
+    }
+}
+
+impl MakeWidget for check_button::TkCheckButton {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        check_button::make_check_button(parent)
+    }
+}
+
+impl MakeWidget for entry::TkEntry {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        entry::make_entry(parent)
+    }
+}
+
+impl MakeWidget for frame::TkFrame {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        frame::make_frame(parent)
+    }
+}
+
+impl MakeWidget for label::TkLabel {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        label::make_label(parent)
+    }
+}
+
+impl MakeWidget for label_frame::TkLabelFrame {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        label_frame::make_label_frame(parent)
+    }
+}
+
+impl MakeWidget for log_view::TkLogView {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        log_view::make_log_view(parent)
+    }
+}
+
+impl MakeWidget for notebook::TkNotebook {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        notebook::make_notebook(parent)
+    }
+}
+
+impl MakeWidget for text::TkText {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        text::make_text(parent)
+    }
+}
+
+impl MakeWidget for treeview::TkTreeview {
+    fn make(parent: &impl widget::TkWidget) -> Self {
+        treeview::make_treeview(parent)
+    }
+}
+
+/// Declares a struct of widgets, with a `new` function creating them: see
+/// the [ui](crate::ui) module.
+#[macro_export]
+macro_rules! ui {
+    (@parent $default:ident) => { $default };
+    (@parent $default:ident, $parent:ident) => { &$parent };
+
+    (@make $ty:ident, $parent:expr) => {
+        <$crate::$ty as $crate::ui::MakeWidget>::make($parent)
+    };
+    (@make $ty:ident, $parent:expr, $ctor:ident ( $( $arg:expr ),* )) => {
+        $crate::$ctor($parent, $( $arg ),*)
+    };
+
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $field:ident : $ty:ident
+                $( in $parent:ident )?
+                $( = $ctor:ident ( $( $carg:expr ),* $(,)? ) )?
+                { $( $method:ident ( $( $arg:expr ),* $(,)? ) ),* $(,)? }
+                $( $layout:ident { $( $lmethod:ident ( $( $larg:expr ),* $(,)? ) ),* $(,)? } )?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $( pub $field: $crate::$ty, )*
+        }
+
+        impl $name {
+            /// Creates the widgets in given parent.
+            #[allow(unused_imports)]
+            $vis fn new(parent: &impl $crate::widget::TkWidget) -> $name {
+                use $crate::grid::TkGridLayout;
+                use $crate::pack::TkPackLayout;
+                use $crate::place::TkPlaceLayout;
+                use $crate::widget::{TkLabelOptions, TkWidget};
+
+                $(
+                    let $field = $crate::ui!(
+                        @make $ty,
+                        $crate::ui!(@parent parent $(, $parent)?)
+                        $(, $ctor ( $( $carg ),* ))?
+                    );
+                    $( $field.$method( $( $arg ),* ); )*
+                    $( $field.$layout() $( .$lmethod( $( $larg ),* ) )* .layout(); )?
+                )*
+
+                $name { $( $field, )* }
+            }
+        }
+    };
+}
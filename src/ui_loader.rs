@@ -0,0 +1,816 @@
+//! UI loader - creates widgets described in a JSON file.
+//!
+//! Available with the "ui_loader" feature. A dialog's widgets, their
+//! options and layouts are described in JSON, and created at runtime by
+//! [load_ui], so a form can be changed without recompiling:
+//!
+//! ```json
+//! [
+//!   { "name": "name_label", "type": "label",
+//!     "options": { "text": "Name:" },
+//!     "grid": { "row": 0, "column": 0, "sticky": "e" } },
+//!   { "name": "name", "type": "entry",
+//!     "grid": { "row": 0, "column": 1, "sticky": "ew" } },
+//!   { "name": "buttons", "type": "frame",
+//!     "grid": { "row": 1, "column": 1, "sticky": "e" },
+//!     "children": [
+//!       { "name": "ok", "type": "button",
+//!         "options": { "text": "OK", "command": "submit" },
+//!         "pack": { "side": "left", "padx": 5 } }
+//!     ] }
+//! ]
+//! ```
+//!
+//! ```ignore
+//! let mut callbacks = UiCallbacks::default();
+//! callbacks.add("submit", || println!("submitted"));
+//!
+//! let widgets = rish::load_ui_file(&root, "login.json", &callbacks)?;
+//! if let Some(UiWidget::Entry(name)) = widgets.get("name") {
+//!     name.focus();
+//! }
+//! ```
+//!
+//! Each widget has a unique "name", a "type", and optionally:
+//!
+//! * "options" - Tk options for the widget: see [WIDGET_TYPES] for those
+//!   allowed for each type. A "command" names a callback given in
+//!   [UiCallbacks]; "values" lists the entries of a combobox or listbox, and
+//!   "columns" and "headings" the columns of a treeview.
+//! * one of "grid", "pack" or "place" - the layout of the widget, with the
+//!   options of [GridLayout](crate::GridLayout),
+//!   [PackLayout](crate::PackLayout) or [PlaceLayout](crate::PlaceLayout),
+//!   named as in Tk.
+//! * "children" - widgets created inside a frame, label_frame or notebook.
+//!   The children of a notebook each have a "tab" title instead of a layout.
+//!
+//! The whole description is checked before any widget is created: an error
+//! names the widget and the option at fault.
+//!
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+
+use super::button;
+use super::check_button;
+use super::combobox;
+use super::entry;
+use super::frame;
+use super::grid;
+use super::label;
+use super::label_frame;
+use super::listbox;
+use super::notebook;
+use super::pack;
+use super::place;
+use super::text;
+use super::treeview;
+use super::widget;
+use super::wish;
+
+/// The widget types which can be loaded, with the options allowed for each.
+pub const WIDGET_TYPES: &[(&str, &[&str])] = &[
+    (
+        "button",
+        &[
+            "command",
+            "compound",
+            "padding",
+            "state",
+            "text",
+            "underline",
+            "width",
+        ],
+    ),
+    (
+        "check_button",
+        &["command", "padding", "state", "text", "underline", "width"],
+    ),
+    (
+        "combobox",
+        &["height", "justify", "state", "values", "width"],
+    ),
+    (
+        "entry",
+        &["font", "foreground", "justify", "show", "state", "width"],
+    ),
+    (
+        "frame",
+        &["borderwidth", "height", "padding", "relief", "width"],
+    ),
+    (
+        "label",
+        &[
+            "anchor",
+            "background",
+            "compound",
+            "font",
+            "foreground",
+            "justify",
+            "padding",
+            "relief",
+            "text",
+            "underline",
+            "width",
+            "wraplength",
+        ],
+    ),
+    (
+        "label_frame",
+        &[
+            "borderwidth",
+            "height",
+            "labelanchor",
+            "padding",
+            "relief",
+            "text",
+            "underline",
+            "width",
+        ],
+    ),
+    ("listbox", &["height", "selectmode", "values", "width"]),
+    ("notebook", &["height", "padding", "width"]),
+    (
+        "text",
+        &[
+            "background",
+            "font",
+            "foreground",
+            "height",
+            "undo",
+            "width",
+            "wrap",
+        ],
+    ),
+    (
+        "treeview",
+        &["columns", "headings", "height", "selectmode", "show"],
+    ),
+];
+
+// Widget types which can have children.
+const CONTAINERS: &[&str] = &["frame", "label_frame", "notebook"];
+
+// Kinds of value taken by a layout option.
+enum LayoutValue {
+    Anchor,
+    Bool,
+    BorderMode,
+    Fill,
+    Float,
+    Int,
+    Side,
+    Sticky,
+    Uint,
+}
+
+const GRID_OPTIONS: &[(&str, LayoutValue)] = &[
+    ("column", LayoutValue::Uint),
+    ("columnspan", LayoutValue::Uint),
+    ("ipadx", LayoutValue::Uint),
+    ("ipady", LayoutValue::Uint),
+    ("padx", LayoutValue::Uint),
+    ("pady", LayoutValue::Uint),
+    ("row", LayoutValue::Uint),
+    ("rowspan", LayoutValue::Uint),
+    ("sticky", LayoutValue::Sticky),
+];
+
+const PACK_OPTIONS: &[(&str, LayoutValue)] = &[
+    ("anchor", LayoutValue::Anchor),
+    ("expand", LayoutValue::Bool),
+    ("fill", LayoutValue::Fill),
+    ("ipadx", LayoutValue::Uint),
+    ("ipady", LayoutValue::Uint),
+    ("padx", LayoutValue::Uint),
+    ("pady", LayoutValue::Uint),
+    ("side", LayoutValue::Side),
+];
+
+const PLACE_OPTIONS: &[(&str, LayoutValue)] = &[
+    ("anchor", LayoutValue::Anchor),
+    ("bordermode", LayoutValue::BorderMode),
+    ("height", LayoutValue::Uint),
+    ("relheight", LayoutValue::Float),
+    ("relwidth", LayoutValue::Float),
+    ("relx", LayoutValue::Float),
+    ("rely", LayoutValue::Float),
+    ("width", LayoutValue::Uint),
+    ("x", LayoutValue::Int),
+    ("y", LayoutValue::Int),
+];
+
+/// A widget created by [load_ui].
+#[derive(Clone)]
+pub enum UiWidget {
+    Button(button::TkButton),
+    CheckButton(check_button::TkCheckButton),
+    Combobox(combobox::TkCombobox),
+    Entry(entry::TkEntry),
+    Frame(frame::TkFrame),
+    Label(label::TkLabel),
+    LabelFrame(label_frame::TkLabelFrame),
+    Listbox(listbox::TkListbox),
+    Notebook(notebook::TkNotebook),
+    Text(text::TkText),
+    Treeview(treeview::TkTreeview),
+}
+
+impl widget::TkWidget for UiWidget {
+    /// Returns the widget's id reference - used within tk
+    fn id(&self) -> &str {
+        match self {
+            UiWidget::Button(widget) => &widget.id,
+            UiWidget::CheckButton(widget) => &widget.id,
+            UiWidget::Combobox(widget) => &widget.id,
+            UiWidget::Entry(widget) => &widget.id,
+            UiWidget::Frame(widget) => &widget.id,
+            UiWidget::Label(widget) => &widget.id,
+            UiWidget::LabelFrame(widget) => &widget.id,
+            UiWidget::Listbox(widget) => &widget.id,
+            UiWidget::Notebook(widget) => &widget.id,
+            UiWidget::Text(widget) => &widget.id,
+            UiWidget::Treeview(widget) => &widget.id,
+        }
+    }
+}
+
+impl grid::TkGridLayout for UiWidget {}
+impl pack::TkPackLayout for UiWidget {}
+impl place::TkPlaceLayout for UiWidget {}
+
+/// The named functions which widgets loaded by [load_ui] can use as their
+/// "command".
+#[derive(Clone, Default)]
+pub struct UiCallbacks {
+    callbacks: HashMap<String, Arc<dyn Fn() + Send + Sync>>,
+}
+
+impl UiCallbacks {
+    /// Adds a function, to be called by widgets with the given command name.
+    pub fn add(&mut self, name: &str, command: impl Fn() + Send + Sync + 'static) -> &mut Self {
+        self.callbacks.insert(String::from(name), Arc::new(command));
+        self
+    }
+}
+
+// A checked description of a widget.
+#[derive(Debug)]
+struct WidgetSpec {
+    name: String,
+    kind: String,
+    options: Vec<(String, Value)>,
+    layout: Option<(String, Vec<(String, Value)>)>,
+    tab: Option<String>,
+    children: Vec<WidgetSpec>,
+}
+
+/// Creates the widgets described by the JSON in source, inside parent,
+/// returning the widgets by name.
+pub fn load_ui(
+    parent: &impl widget::TkWidget,
+    source: &str,
+    callbacks: &UiCallbacks,
+) -> Result<HashMap<String, UiWidget>, wish::TkError> {
+    let specs = parse_ui(source, callbacks)?;
+    let mut widgets = HashMap::new();
+
+    for spec in &specs {
+        create(parent, None, spec, callbacks, &mut widgets);
+    }
+
+    Ok(widgets)
+}
+
+/// Creates the widgets described by the JSON file at path, inside parent,
+/// returning the widgets by name.
+pub fn load_ui_file(
+    parent: &impl widget::TkWidget,
+    path: impl AsRef<Path>,
+    callbacks: &UiCallbacks,
+) -> Result<HashMap<String, UiWidget>, wish::TkError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|error| {
+        wish::TkError::new(&format!("Cannot read {}: {}", path.display(), error))
+    })?;
+    load_ui(parent, &source, callbacks)
+}
+
+// Reads and checks the description of the widgets.
+fn parse_ui(source: &str, callbacks: &UiCallbacks) -> Result<Vec<WidgetSpec>, wish::TkError> {
+    let value: Value = serde_json::from_str(source)
+        .map_err(|error| wish::TkError::new(&format!("Invalid UI description: {}", error)))?;
+    let mut names = vec![];
+
+    parse_widgets(&value, None, callbacks, &mut names)
+}
+
+fn parse_widgets(
+    value: &Value,
+    parent_kind: Option<&str>,
+    callbacks: &UiCallbacks,
+    names: &mut Vec<String>,
+) -> Result<Vec<WidgetSpec>, wish::TkError> {
+    match value.as_array() {
+        Some(items) => items
+            .iter()
+            .map(|item| parse_widget(item, parent_kind, callbacks, names))
+            .collect(),
+        None => Err(wish::TkError::new("Expected a list of widgets")),
+    }
+}
+
+fn parse_widget(
+    value: &Value,
+    parent_kind: Option<&str>,
+    callbacks: &UiCallbacks,
+    names: &mut Vec<String>,
+) -> Result<WidgetSpec, wish::TkError> {
+    let fail = |message: String| Err(wish::TkError::new(&message));
+
+    let object = match value.as_object() {
+        Some(object) => object,
+        None => return fail(format!("Expected a widget, found: {}", value)),
+    };
+    let name = match object.get("name").and_then(Value::as_str) {
+        Some(name) => String::from(name),
+        None => return fail(format!("Widget has no \"name\": {}", value)),
+    };
+    if names.contains(&name) {
+        return fail(format!("Widget name '{}' is used more than once", name));
+    }
+    names.push(name.clone());
+
+    let kind = object.get("type").and_then(Value::as_str).unwrap_or("");
+    let allowed = match WIDGET_TYPES.iter().find(|(known, _)| *known == kind) {
+        Some((_, allowed)) => *allowed,
+        None => {
+            let known: Vec<&str> = WIDGET_TYPES.iter().map(|(known, _)| *known).collect();
+            return fail(format!(
+                "Unknown widget type '{}' for '{}': expected one of {}",
+                kind,
+                name,
+                known.join(", ")
+            ));
+        }
+    };
+
+    let mut spec = WidgetSpec {
+        name: name.clone(),
+        kind: String::from(kind),
+        options: vec![],
+        layout: None,
+        tab: None,
+        children: vec![],
+    };
+
+    for (key, value) in object {
+        match key.as_str() {
+            "name" | "type" => {}
+            "options" => {
+                spec.options = parse_options(&name, kind, allowed, value, callbacks)?;
+            }
+            "grid" | "pack" | "place" => {
+                if spec.layout.is_some() {
+                    return fail(format!("Widget '{}' has more than one layout", name));
+                }
+                spec.layout = Some((key.clone(), parse_layout(&name, key, value)?));
+            }
+            "tab" => match (parent_kind, value.as_str()) {
+                (Some("notebook"), Some(title)) => spec.tab = Some(String::from(title)),
+                (Some("notebook"), None) => {
+                    return fail(format!("The \"tab\" of '{}' must be a string", name))
+                }
+                _ => {
+                    return fail(format!(
+                        "Widget '{}' has a \"tab\", but is not in a notebook",
+                        name
+                    ))
+                }
+            },
+            "children" => {
+                if !CONTAINERS.contains(&kind) {
+                    return fail(format!(
+                        "Widget '{}' cannot have children: only {} can",
+                        name,
+                        CONTAINERS.join(", ")
+                    ));
+                }
+                spec.children = parse_widgets(value, Some(kind), callbacks, names)?;
+            }
+            _ => {
+                return fail(format!(
+                    "Unknown key '{}' for widget '{}': expected name, type, options, grid, pack, place, tab or children",
+                    key, name
+                ))
+            }
+        }
+    }
+
+    if parent_kind == Some("notebook") && spec.tab.is_none() {
+        return fail(format!("Widget '{}' in a notebook needs a \"tab\"", name));
+    }
+    if spec.tab.is_some() && spec.layout.is_some() {
+        return fail(format!(
+            "Widget '{}' has a \"tab\", so cannot also have a layout",
+            name
+        ));
+    }
+
+    Ok(spec)
+}
+
+fn parse_options(
+    name: &str,
+    kind: &str,
+    allowed: &[&str],
+    value: &Value,
+    callbacks: &UiCallbacks,
+) -> Result<Vec<(String, Value)>, wish::TkError> {
+    let fail = |message: String| Err(wish::TkError::new(&message));
+    let object = match value.as_object() {
+        Some(object) => object,
+        None => return fail(format!("The \"options\" of '{}' must be an object", name)),
+    };
+    let mut options = vec![];
+
+    for (option, value) in object {
+        if !allowed.contains(&option.as_str()) {
+            return fail(format!(
+                "Unknown option '{}' for {} '{}': expected one of {}",
+                option,
+                kind,
+                name,
+                allowed.join(", ")
+            ));
+        }
+        let valid = match option.as_str() {
+            "command" => match value.as_str() {
+                Some(command) if callbacks.callbacks.contains_key(command) => true,
+                Some(command) => {
+                    return fail(format!(
+                        "Unknown callback '{}' for the command of '{}'",
+                        command, name
+                    ))
+                }
+                None => false,
+            },
+            "columns" | "headings" | "values" => value
+                .as_array()
+                .is_some_and(|items| items.iter().all(Value::is_string)),
+            _ => tcl_value(value).is_some(),
+        };
+        if !valid {
+            return fail(format!(
+                "Invalid value for option '{}' of '{}': {}",
+                option, name, value
+            ));
+        }
+        options.push((option.clone(), value.clone()));
+    }
+
+    Ok(options)
+}
+
+fn parse_layout(
+    name: &str,
+    layout: &str,
+    value: &Value,
+) -> Result<Vec<(String, Value)>, wish::TkError> {
+    let fail = |message: String| Err(wish::TkError::new(&message));
+    let allowed = match layout {
+        "grid" => GRID_OPTIONS,
+        "pack" => PACK_OPTIONS,
+        _ => PLACE_OPTIONS,
+    };
+    let object: &Map<String, Value> = match value.as_object() {
+        Some(object) => object,
+        None => {
+            return fail(format!(
+                "The \"{}\" layout of '{}' must be an object",
+                layout, name
+            ))
+        }
+    };
+    let mut options = vec![];
+
+    for (option, value) in object {
+        let kind = match allowed.iter().find(|(known, _)| known == option) {
+            Some((_, kind)) => kind,
+            None => {
+                let known: Vec<&str> = allowed.iter().map(|(known, _)| *known).collect();
+                return fail(format!(
+                    "Unknown {} option '{}' for '{}': expected one of {}",
+                    layout,
+                    option,
+                    name,
+                    known.join(", ")
+                ));
+            }
+        };
+        let text = value.as_str().unwrap_or("");
+        let valid = match kind {
+            LayoutValue::Anchor => {
+                ["n", "ne", "e", "se", "s", "sw", "w", "nw", "center"].contains(&text)
+            }
+            LayoutValue::Bool => value.is_boolean(),
+            LayoutValue::BorderMode => ["ignore", "inside", "outside"].contains(&text),
+            LayoutValue::Fill => ["both", "none", "x", "y"].contains(&text),
+            LayoutValue::Float => value.is_f64() || value.is_i64() || value.is_u64(),
+            LayoutValue::Int => value.is_i64() || value.is_u64(),
+            LayoutValue::Side => ["bottom", "left", "right", "top"].contains(&text),
+            LayoutValue::Sticky => value.is_string() && text.chars().all(|c| "nesw".contains(c)),
+            LayoutValue::Uint => value.is_u64(),
+        };
+        if !valid {
+            return fail(format!(
+                "Invalid value for {} option '{}' of '{}': {}",
+                layout, option, name, value
+            ));
+        }
+        options.push((option.clone(), value.clone()));
+    }
+
+    Ok(options)
+}
+
+// Returns the value as a tcl value: lists of values are written as a tcl list.
+fn tcl_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Bool(flag) => Some(String::from(if *flag { "1" } else { "0" })),
+        Value::Number(number) => Some(number.to_string()),
+        Value::String(text) => Some(text.clone()),
+        Value::Array(items) => {
+            let items: Option<Vec<String>> = items
+                .iter()
+                .map(|item| match item {
+                    Value::Array(_) => None,
+                    _ => tcl_value(item).map(|item| wish::tcl_quote(&item)),
+                })
+                .collect();
+            items.map(|items| items.join(" "))
+        }
+        _ => None,
+    }
+}
+
+// Returns the strings in a list option.
+fn strings(value: &Value) -> Vec<&str> {
+    value
+        .as_array()
+        .map(|items| items.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+// Creates the widget and its children, adding them to widgets.
+fn create(
+    parent: &impl widget::TkWidget,
+    notebook: Option<&notebook::TkNotebook>,
+    spec: &WidgetSpec,
+    callbacks: &UiCallbacks,
+    widgets: &mut HashMap<String, UiWidget>,
+) {
+    let option = |name: &str| {
+        spec.options
+            .iter()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| value)
+    };
+    let values = option("values").map(strings).unwrap_or_default();
+    let command = option("command")
+        .and_then(Value::as_str)
+        .and_then(|command| callbacks.callbacks.get(command))
+        .cloned();
+
+    let created = match spec.kind.as_str() {
+        "button" => {
+            let button = button::make_button(parent);
+            if let Some(command) = command {
+                button.command(move || command());
+            }
+            UiWidget::Button(button)
+        }
+        "check_button" => {
+            let check_button = check_button::make_check_button(parent);
+            if let Some(command) = command {
+                check_button.command(move |_| command());
+            }
+            UiWidget::CheckButton(check_button)
+        }
+        "combobox" => UiWidget::Combobox(combobox::make_combobox(parent, &values)),
+        "entry" => UiWidget::Entry(entry::make_entry(parent)),
+        "frame" => UiWidget::Frame(frame::make_frame(parent)),
+        "label" => UiWidget::Label(label::make_label(parent)),
+        "label_frame" => UiWidget::LabelFrame(label_frame::make_label_frame(parent)),
+        "listbox" => UiWidget::Listbox(listbox::make_listbox(parent, &values)),
+        "notebook" => UiWidget::Notebook(notebook::make_notebook(parent)),
+        "text" => UiWidget::Text(text::make_text(parent)),
+        _ => {
+            let treeview = treeview::make_treeview(parent);
+            if let Some(columns) = option("columns") {
+                treeview.columns(&strings(columns));
+            }
+            if let Some(headings) = option("headings") {
+                let columns = option("columns").map(strings).unwrap_or_default();
+                for (column, heading) in columns.iter().zip(strings(headings)) {
+                    treeview.heading_text(column, heading);
+                }
+            }
+            UiWidget::Treeview(treeview)
+        }
+    };
+
+    for (name, value) in &spec.options {
+        if !["columns", "command", "headings", "values"].contains(&name.as_str()) {
+            if let Some(value) = tcl_value(value) {
+                let msg = format!(
+                    "{} configure -{} {}",
+                    widget::TkWidget::id(&created),
+                    name,
+                    wish::tcl_quote(&value)
+                );
+                wish::tell_wish(&msg);
+            }
+        }
+    }
+
+    if let (Some(notebook), Some(title)) = (notebook, &spec.tab) {
+        notebook.add(&created, title);
+    }
+    if let Some((layout, options)) = &spec.layout {
+        apply_layout(&created, layout, options);
+    }
+
+    let child_notebook = match &created {
+        UiWidget::Notebook(notebook) => Some(notebook.clone()),
+        _ => None,
+    };
+    for child in &spec.children {
+        create(&created, child_notebook.as_ref(), child, callbacks, widgets);
+    }
+
+    widgets.insert(spec.name.clone(), created);
+}
+
+// Lays out the widget with the options, already checked by parse_layout.
+fn apply_layout(created: &UiWidget, layout: &str, options: &[(String, Value)]) {
+    use grid::TkGridLayout;
+    use pack::TkPackLayout;
+    use place::TkPlaceLayout;
+
+    match layout {
+        "grid" => {
+            let mut builder = created.grid();
+            for (option, value) in options {
+                let number = value.as_u64().unwrap_or(0);
+                match option.as_str() {
+                    "column" => builder.column(number),
+                    "columnspan" => builder.column_span(number),
+                    "ipadx" => builder.ipadx(number),
+                    "ipady" => builder.ipady(number),
+                    "padx" => builder.padx(number),
+                    "pady" => builder.pady(number),
+                    "row" => builder.row(number),
+                    "rowspan" => builder.row_span(number),
+                    _ => builder.sticky(widget::Sticky::from_name(value.as_str().unwrap_or(""))),
+                };
+            }
+            builder.layout();
+        }
+        "pack" => {
+            let mut builder = created.pack();
+            for (option, value) in options {
+                let number = value.as_u64().unwrap_or(0);
+                let text = value.as_str().unwrap_or("");
+                match option.as_str() {
+                    "anchor" => builder.anchor(widget::Anchor::from_name(text)),
+                    "expand" => builder.expand(value.as_bool().unwrap_or(false)),
+                    "fill" => builder.fill(match text {
+                        "both" => widget::PackFill::Both,
+                        "x" => widget::PackFill::X,
+                        "y" => widget::PackFill::Y,
+                        _ => widget::PackFill::None,
+                    }),
+                    "ipadx" => builder.ipadx(number),
+                    "ipady" => builder.ipady(number),
+                    "padx" => builder.padx(number),
+                    "pady" => builder.pady(number),
+                    _ => builder.side(match text {
+                        "bottom" => widget::PackSide::Bottom,
+                        "left" => widget::PackSide::Left,
+                        "right" => widget::PackSide::Right,
+                        _ => widget::PackSide::Top,
+                    }),
+                };
+            }
+            builder.layout();
+        }
+        _ => {
+            let mut builder = created.place();
+            for (option, value) in options {
+                let number = value.as_u64().unwrap_or(0);
+                let float = value.as_f64().unwrap_or(0.0);
+                let int = value.as_i64().unwrap_or(0);
+                match option.as_str() {
+                    "anchor" => {
+                        builder.anchor(widget::Anchor::from_name(value.as_str().unwrap_or("")))
+                    }
+                    "bordermode" => builder
+                        .border_mode(widget::BorderMode::from_name(value.as_str().unwrap_or(""))),
+                    "height" => builder.height(number),
+                    "relheight" => builder.relheight(float),
+                    "relwidth" => builder.relwidth(float),
+                    "relx" => builder.relx(float),
+                    "rely" => builder.rely(float),
+                    "width" => builder.width(number),
+                    "x" => builder.x(int),
+                    _ => builder.y(int),
+                };
+            }
+            builder.layout();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> String {
+        let mut callbacks = UiCallbacks::default();
+        callbacks.add("submit", || {});
+        parse_ui(source, &callbacks).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn ui_parse() {
+        let mut callbacks = UiCallbacks::default();
+        callbacks.add("submit", || {});
+        let specs = parse_ui(
+            r#"[
+                {"name": "tabs", "type": "notebook", "pack": {"fill": "both", "expand": true},
+                 "children": [
+                    {"name": "page", "type": "frame", "tab": "Page 1", "children": [
+                        {"name": "ok", "type": "button",
+                         "options": {"text": "OK", "command": "submit", "padding": [5, 2]},
+                         "grid": {"row": 1, "sticky": "nsew"}}
+                    ]}
+                 ]}
+            ]"#,
+            &callbacks,
+        )
+        .unwrap();
+        assert_eq!(1, specs.len());
+        let page = &specs[0].children[0];
+        assert_eq!(Some(String::from("Page 1")), page.tab);
+        assert_eq!("button", page.children[0].kind);
+        assert_eq!(3, page.children[0].options.len());
+        let padding = page.children[0]
+            .options
+            .iter()
+            .find(|(option, _)| option == "padding")
+            .and_then(|(_, value)| tcl_value(value));
+        assert_eq!(Some(String::from("5 2")), padding);
+    }
+
+    #[test]
+    fn ui_parse_errors() {
+        assert!(error("[{").starts_with("Invalid UI description"));
+        assert!(error(r#"[{"name": "a", "type": "buton"}]"#)
+            .starts_with("Unknown widget type 'buton' for 'a'"));
+        assert!(
+            error(r#"[{"name": "a", "type": "label", "options": {"colour": "red"}}]"#)
+                .starts_with("Unknown option 'colour' for label 'a'")
+        );
+        assert!(
+            error(r#"[{"name": "a", "type": "button", "options": {"command": "quit"}}]"#)
+                .starts_with("Unknown callback 'quit'")
+        );
+        assert!(
+            error(r#"[{"name": "a", "type": "label", "grid": {"row": -1}}]"#)
+                .starts_with("Invalid value for grid option 'row'")
+        );
+        assert!(
+            error(r#"[{"name": "a", "type": "label", "grid": {}, "pack": {}}]"#)
+                .ends_with("more than one layout")
+        );
+        assert!(
+            error(r#"[{"name": "a", "type": "label"}, {"name": "a", "type": "entry"}]"#)
+                .ends_with("used more than once")
+        );
+        assert!(error(r#"[{"name": "a", "type": "label", "children": []}]"#)
+            .starts_with("Widget 'a' cannot have children"));
+        assert!(error(
+            r#"[{"name": "n", "type": "notebook", "children": [{"name": "a", "type": "frame"}]}]"#
+        )
+        .ends_with("needs a \"tab\""));
+        assert!(error(
+            r#"[{"name": "n", "type": "notebook", "children": [{"name": "a", "type": "frame", "tab": "A", "pack": {}}]}]"#
+        )
+        .ends_with("cannot also have a layout"));
+    }
+}
@@ -3,9 +3,11 @@
 //!
 
 use std::fmt;
+use std::sync::Mutex;
 
 use super::canvas;
 use super::font;
+use super::grid;
 use super::image;
 use super::wish;
 use crate::chart::plotchart;
@@ -88,6 +90,35 @@ pub trait TkWidget {
         wish::tell_wish(&msg);
     }
 
+    // -- grabs: directing all input to one window
+
+    /// Releases the grab on this widget, if it has one.
+    fn grab_release(&self) {
+        let msg = format!("grab release {}", self.id());
+        wish::tell_wish(&msg);
+    }
+
+    /// Directs all input in this application to this widget and its
+    /// descendants: other windows ignore the mouse and keyboard. The widget
+    /// must be visible.
+    fn grab_set(&self) -> Result<(), wish::TkError> {
+        wish::eval_wish(&format!("grab set {}", self.id()))?;
+        Ok(())
+    }
+
+    /// Directs all input on the screen to this widget and its descendants:
+    /// use with care, as other applications cannot be used meanwhile.
+    fn grab_set_global(&self) -> Result<(), wish::TkError> {
+        wish::eval_wish(&format!("grab set -global {}", self.id()))?;
+        Ok(())
+    }
+
+    /// Returns the kind of grab on this widget, or None if it has no grab.
+    fn grab_status(&self) -> Option<GrabStatus> {
+        let msg = format!("puts [grab status {}] ; flush stdout", self.id());
+        GrabStatus::from_name(&wish::ask_wish(&msg))
+    }
+
     // -- winfo functions
 
     /// Returns the widget x position in pixels, within its parent.
@@ -221,6 +252,103 @@ pub trait TkWidget {
 
     // -- for widgets that can contain other widgets
 
+    /// Returns the bounding box (x, y, width, height) of the given cell of
+    /// the grid layout contained within this widget.
+    fn grid_bbox(&self, column: u64, row: u64) -> (i64, i64, i64, i64) {
+        let msg = format!(
+            "puts [grid bbox {} {} {}] ; flush stdout",
+            self.id(),
+            column,
+            row
+        );
+        let result = wish::ask_wish(&msg);
+        let parts: Vec<i64> = result
+            .split_whitespace()
+            .filter_map(|part| part.parse::<i64>().ok())
+            .collect();
+        if parts.len() == 4 {
+            (parts[0], parts[1], parts[2], parts[3])
+        } else {
+            (0, 0, 0, 0)
+        }
+    }
+
+    /// Returns settings for a given column of the grid layout contained
+    /// within this widget, to set in a builder style.
+    fn grid_column(&self, index: u64) -> grid::GridConfig {
+        grid::GridConfig::new("columnconfigure", self.id(), index)
+    }
+
+    /// Returns the (column, row) of the grid layout contained within this
+    /// widget at the given position: -1 if the position is before the
+    /// first column or row.
+    fn grid_location(&self, x: i64, y: i64) -> (i64, i64) {
+        let msg = format!(
+            "puts [grid location {} {} {}] ; flush stdout",
+            self.id(),
+            x,
+            y
+        );
+        let result = wish::ask_wish(&msg);
+        let parts: Vec<i64> = result
+            .split_whitespace()
+            .filter_map(|part| part.parse::<i64>().ok())
+            .collect();
+        if parts.len() == 2 {
+            (parts[0], parts[1])
+        } else {
+            (-1, -1)
+        }
+    }
+
+    /// Sets whether this widget resizes to fit the widgets in its grid
+    /// layout (the default), or keeps its own size.
+    fn grid_propagate(&self, value: bool) {
+        let msg = format!(
+            "grid propagate {} {}",
+            self.id(),
+            if value { "1" } else { "0" }
+        );
+        wish::tell_wish(&msg);
+    }
+
+    /// Returns settings for a given row of the grid layout contained
+    /// within this widget, to set in a builder style.
+    fn grid_row(&self, index: u64) -> grid::GridConfig {
+        grid::GridConfig::new("rowconfigure", self.id(), index)
+    }
+
+    /// Returns the (columns, rows) size of the grid layout contained
+    /// within this widget.
+    fn grid_size(&self) -> (u64, u64) {
+        let msg = format!("puts [grid size {}] ; flush stdout", self.id());
+        let result = wish::ask_wish(&msg);
+        let parts: Vec<u64> = result
+            .split_whitespace()
+            .filter_map(|part| part.parse::<u64>().ok())
+            .collect();
+        if parts.len() == 2 {
+            (parts[0], parts[1])
+        } else {
+            (0, 0)
+        }
+    }
+
+    /// Returns the ids of the widgets in the grid layout contained within
+    /// this widget, optionally only those in the given row and/or column.
+    fn grid_slaves(&self, row: Option<u64>, column: Option<u64>) -> Vec<String> {
+        let mut msg = format!("puts [grid slaves {}", self.id());
+        if let Some(row) = row {
+            msg.push_str(&format!(" -row {}", row));
+        }
+        if let Some(column) = column {
+            msg.push_str(&format!(" -column {}", column));
+        }
+        msg.push_str("] ; flush stdout");
+        let result = wish::ask_wish(&msg);
+        wish::split_items(&result)
+    }
+
     /// Sets property for a given column of the grid layout
     /// contained within this widget.
     fn grid_configure_column(&self, index: u64, option: &str, value: &str) {
@@ -246,6 +374,96 @@ pub trait TkWidget {
         );
         wish::tell_wish(&msg);
     }
+
+    // -- lifecycle events, for this widget alone
+
+    /// Calls the command once this widget is destroyed, then releases the
+    /// callbacks rish holds for this widget and its descendants.
+    fn on_destroy(&self, command: impl Fn() + Send + 'static) {
+        let wid = String::from(self.id());
+        bind_window(self.id(), "destroy", "<Destroy>", move |_| {
+            command();
+            // -- once the current callback has finished
+            let wid = wid.clone();
+            after(0, move || wish::remove_callbacks(&wid));
+        });
+    }
+
+    /// Calls the command when this widget gains the keyboard focus.
+    fn on_focus_in(&self, command: impl Fn() + Send + 'static) {
+        bind_window(self.id(), "focusin", "<FocusIn>", move |_| command());
+    }
+
+    /// Calls the command when this widget loses the keyboard focus.
+    fn on_focus_out(&self, command: impl Fn() + Send + 'static) {
+        bind_window(self.id(), "focusout", "<FocusOut>", move |_| command());
+    }
+
+    /// Calls the command when this widget is shown, e.g. when laid out or
+    /// de-iconified.
+    fn on_map(&self, command: impl Fn() + Send + 'static) {
+        bind_window(self.id(), "map", "<Map>", move |_| command());
+    }
+
+    /// Calls the command with the new position (x, y) of this widget
+    /// whenever it moves: the position is within its parent or, for a
+    /// top-level window, on the screen.
+    fn on_move(&self, command: impl Fn(i64, i64) + Send + 'static) {
+        let last = Mutex::new(None);
+        bind_window(self.id(), "move", "<Configure>", move |event| {
+            let position = (event.x, event.y);
+            if last.lock().unwrap().replace(position) != Some(position) {
+                command(event.x, event.y);
+            }
+        });
+    }
+
+    /// Calls the command with the new size (width, height) of this widget
+    /// whenever it changes, in pixels.
+    fn on_resize(&self, command: impl Fn(u64, u64) + Send + 'static) {
+        let last = Mutex::new(None);
+        bind_window(self.id(), "resize", "<Configure>", move |event| {
+            let size = (event.width.max(0) as u64, event.height.max(0) as u64);
+            if last.lock().unwrap().replace(size) != Some(size) {
+                command(size.0, size.1);
+            }
+        });
+    }
+
+    /// Calls the command when this widget is hidden, e.g. when removed from
+    /// its layout or iconified.
+    fn on_unmap(&self, command: impl Fn() + Send + 'static) {
+        bind_window(self.id(), "unmap", "<Unmap>", move |_| command());
+    }
+
+    // -- waiting, while processing events
+
+    /// Waits until this widget is visible, processing events meanwhile:
+    /// returns at once if it is already visible, or does not exist.
+    fn wait_visibility(&self) {
+        let key = format!("waitvisibility{}", self.id());
+        let msg = format!(
+            "if {{[winfo exists {id}] && ![winfo viewable {id}]}} \
+             {{rish_wait {id} {{<Visibility> <Destroy>}} {key}}} \
+             else {{rish_wait_done {id} {key}}}",
+            id = self.id(),
+            key = key
+        );
+        wish::wait_for(&key, &msg);
+    }
+
+    /// Waits until this widget is destroyed, processing events meanwhile:
+    /// returns at once if it does not exist.
+    fn wait_window(&self) {
+        let key = format!("waitwindow{}", self.id());
+        let msg = format!(
+            "if {{[winfo exists {id}]}} {{rish_wait {id} <Destroy> {key}}} \
+             else {{rish_wait_done {id} {key}}}",
+            id = self.id(),
+            key = key
+        );
+        wish::wait_for(&key, &msg);
+    }
 }
 
 /// A set of common functions used in all label, button and similar widgets.
@@ -341,6 +559,57 @@ impl fmt::Display for Anchor {
     }
 }
 
+impl Anchor {
+    // Returns the value with the given tk name.
+    pub(super) fn from_name(name: &str) -> Anchor {
+        match name {
+            "n" => Anchor::N,
+            "ne" => Anchor::NE,
+            "e" => Anchor::E,
+            "se" => Anchor::SE,
+            "s" => Anchor::S,
+            "sw" => Anchor::SW,
+            "w" => Anchor::W,
+            "nw" => Anchor::NW,
+            _ => Anchor::Center,
+        }
+    }
+}
+
+/// Whether the border of a widget's container is included in the area
+/// used by a [place](crate::place) layout.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorderMode {
+    /// the border is ignored: the area is the container's whole window
+    Ignore,
+    /// the area is inside the border
+    Inside,
+    /// the area includes the border
+    Outside,
+}
+
+impl BorderMode {
+    // Returns the value with the given tk name.
+    pub(super) fn from_name(name: &str) -> BorderMode {
+        match name {
+            "ignore" => BorderMode::Ignore,
+            "outside" => BorderMode::Outside,
+            _ => BorderMode::Inside,
+        }
+    }
+}
+
+impl fmt::Display for BorderMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            BorderMode::Ignore => "ignore",
+            BorderMode::Inside => "inside",
+            BorderMode::Outside => "outside",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
 /// Arrangement of image relative to text in a
 /// label-like widget.
 ///
@@ -403,6 +672,26 @@ impl fmt::Display for DialogType {
     }
 }
 
+/// Kind of grab held by a widget: see [grab_status](TkWidget::grab_status).
+#[derive(Clone, Debug, PartialEq)]
+pub enum GrabStatus {
+    /// input on the whole screen is directed to the widget
+    Global,
+    /// input in this application is directed to the widget
+    Local,
+}
+
+impl GrabStatus {
+    // Returns the value with the given tk name, or None if there is no grab.
+    pub(super) fn from_name(name: &str) -> Option<GrabStatus> {
+        match name {
+            "global" => Some(GrabStatus::Global),
+            "local" => Some(GrabStatus::Local),
+            _ => None,
+        }
+    }
+}
+
 /// Type of icon to use in message-box dialog.
 #[derive(Clone, Debug, PartialEq)]
 pub enum IconImage {
@@ -547,6 +836,26 @@ impl fmt::Display for Relief {
     }
 }
 
+/// Defines the units used when scrolling the view of a widget.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScrollUnits {
+    /// Scroll by a number of pages (screenfuls).
+    Pages,
+    /// Scroll by a number of units: lines for text, or the scroll
+    /// increment for a canvas.
+    Units,
+}
+
+impl fmt::Display for ScrollUnits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match self {
+            ScrollUnits::Pages => "pages",
+            ScrollUnits::Units => "units",
+        };
+        write!(f, "{}", &value)
+    }
+}
+
 /// Defines mode of selection.
 ///
 /// Note: rish diverges from the Tk terminology:
@@ -597,6 +906,32 @@ pub enum Sticky {
     None,
 }
 
+impl Sticky {
+    // Returns the value with the given tk name, in which the sides may be
+    // in any order.
+    pub(super) fn from_name(name: &str) -> Sticky {
+        let has = |side| name.contains(side);
+        match (has('n'), has('e'), has('s'), has('w')) {
+            (true, false, false, false) => Sticky::N,
+            (true, true, false, false) => Sticky::NE,
+            (true, true, true, false) => Sticky::NES,
+            (true, true, false, true) => Sticky::NEW,
+            (true, true, true, true) => Sticky::NESW,
+            (true, false, true, false) => Sticky::NS,
+            (true, false, true, true) => Sticky::NSW,
+            (true, false, false, true) => Sticky::NW,
+            (false, true, false, false) => Sticky::E,
+            (false, true, true, false) => Sticky::ES,
+            (false, true, true, true) => Sticky::ESW,
+            (false, true, false, true) => Sticky::EW,
+            (false, false, true, false) => Sticky::S,
+            (false, false, true, true) => Sticky::SW,
+            (false, false, false, true) => Sticky::W,
+            (false, false, false, false) => Sticky::None,
+        }
+    }
+}
+
 impl fmt::Display for Sticky {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let value = match self {
@@ -671,8 +1006,19 @@ impl fmt::Display for Wrapping {
 
 pub(super) fn bind_to(tag: &str, pattern: &str, command: impl Fn(TkEvent) + Send + 'static) {
     // tag+pattern used as identifier, as multiple commands can be bound to each entity
-    let tag_pattern = format!("{}{}", tag, pattern); // TODO ? remove ':' ?
-    wish::add_callback1_event(&tag_pattern, wish::mk_callback1_event(command));
+    let tag_pattern = wish::callback_key(tag, pattern); // TODO ? remove ':' ?
+    bind_keyed(tag, &tag_pattern, pattern, command);
+}
+
+// Binds the command to the event pattern on the tag, storing the command
+// under the given key.
+fn bind_keyed(
+    tag: &str,
+    tag_pattern: &str,
+    pattern: &str,
+    command: impl Fn(TkEvent) + Send + 'static,
+) {
+    wish::add_callback1_event(tag_pattern, wish::mk_callback1_event(command));
     let msg = format!(
         "bind {} {} {{ puts cb1e:{}:%x:%y:%X:%Y:%h:%w:%k:%K:%b ; flush stdout }}",
         tag, pattern, tag_pattern
@@ -680,11 +1026,81 @@ pub(super) fn bind_to(tag: &str, pattern: &str, command: impl Fn(TkEvent) + Send
     wish::tell_wish(&msg);
 }
 
+// Binds the command to events on the given widget alone: a bindtag used
+// only by this widget is added to it, as events on a top-level window's
+// descendants are also bound to the top-level window. Each kind of event
+// has its own bindtag, so several can use the same pattern.
+pub(super) fn bind_window(
+    wid: &str,
+    kind: &str,
+    pattern: &str,
+    command: impl Fn(TkEvent) + Send + 'static,
+) {
+    let tag = format!("{}{}{}", wish::WINDOW_TAG, kind, wid);
+    let msg = format!(
+        "if {{[lsearch -exact [bindtags {wid}] {tag}] < 0}} \
+         {{bindtags {wid} [linsert [bindtags {wid}] 0 {tag}]}}",
+        wid = wid,
+        tag = tag
+    );
+    wish::tell_wish(&msg);
+    let key = wish::callback_key(wid, &format!("{}{}", kind, pattern));
+    bind_keyed(&tag, &key, pattern, command);
+}
+
+// Adds a binding of the event pattern on the widget, reporting the callback0
+// key, to any bindings the widget already has: the key is only bound once.
+pub(super) fn bind_callback0(wid: &str, pattern: &str, key: &str) {
+    let report = format!("puts clicked-{} ; flush stdout", key);
+    let msg = format!(
+        "if {{[string first {} [bind {} {}]] < 0}} {{ bind {} {} {{+{}}} }}",
+        wish::tcl_quote(&report),
+        wid,
+        pattern,
+        wid,
+        pattern,
+        report
+    );
+    wish::tell_wish(&msg);
+}
+
 pub(super) fn configure(wid: &str, option: &str, value: &str) {
     let msg = format!("{} configure -{} {{{}}}", wid, option, value);
     wish::tell_wish(&msg);
 }
 
+// Options set in a builder style, each to be given once as "-option value"
+// in a Tk command.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OptionList {
+    options: Vec<(&'static str, String)>,
+}
+
+impl OptionList {
+    // Sets option to value, replacing any value already set.
+    pub(crate) fn set(&mut self, option: &'static str, value: &str) {
+        self.options.retain(|(name, _)| *name != option);
+        self.options.push((option, String::from(value)));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+}
+
+// Writes the options set, in form "-option value ...", with each value
+// quoted.
+impl fmt::Display for OptionList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options: Vec<String> = self
+            .options
+            .iter()
+            .map(|(option, value)| format!("-{} {}", option, wish::tcl_quote(value)))
+            .collect();
+        write!(f, "{}", options.join(" "))
+    }
+}
+
 pub(super) fn padding(wid: &str, values: &[u64]) {
     let mut value_str = String::new();
     for i in values.iter() {
@@ -694,6 +1110,18 @@ pub(super) fn padding(wid: &str, values: &[u64]) {
     configure(wid, "padding", &value_str);
 }
 
+// Parses the "first last" pair of fractions returned by xview/yview:
+// -- returns (0.0, 1.0), i.e. all visible, if there is a problem
+pub(super) fn view_fractions(text: &str) -> (f64, f64) {
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    if parts.len() == 2 {
+        if let (Ok(first), Ok(last)) = (parts[0].parse::<f64>(), parts[1].parse::<f64>()) {
+            return (first, last);
+        }
+    }
+    (0.0, 1.0)
+}
+
 pub(super) fn strings_list(values: &[&str]) -> String {
     let mut values_str = String::new();
 
@@ -766,6 +1194,22 @@ pub fn plot_pack(
     wish::tell_wish(&msg);
 }
 
+/// Waits until the given Tcl variable is written, processing events
+/// meanwhile.
+pub fn wait_variable(name: &str) {
+    wish::next_wid(".");
+    let key = format!("waitvariable{}", wish::current_id());
+    let trace = format!(
+        "{} write [list rish_wait_trace {}]",
+        wish::tcl_quote(name),
+        key
+    );
+    let msg = format!("trace add variable {}", trace);
+    wish::wait_for(&key, &msg);
+    let msg = format!("trace remove variable {}", trace);
+    wish::tell_wish(&msg);
+}
+
 /// Checks what the current OS system is: see
 /// Tk [manual](https://www.tcl-lang.org/man/tcl8.6/TkCmd/tk.htm#M12).
 ///
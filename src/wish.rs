@@ -40,6 +40,13 @@
 //! The program will usually exit when the top-level window is closed. However,
 //! that can be over-ridden or, to exit in another way, use [end_wish].
 //!
+//! Events are also processed while a callback waits for something to happen
+//! in the GUI, e.g. for a modal dialog to close: see
+//! [run_modal](toplevel::TkTopLevel::run_modal),
+//! [wait_window](widget::TkWidget::wait_window) and
+//! [wait_variable](widget::wait_variable). These run a nested event loop,
+//! returning to the calling code once their wait is over.
+//!
 //! ## Low-level API
 //!
 //! The modules in this crate aim to provide a rust-friendly, type-checked set
@@ -53,6 +60,8 @@
 //! 2. [tell_wish] sends a given string directly to wish
 //! 3. [ask_wish] sends a given string directly to wish and
 //!    returns, as a [String], the response.
+//! 4. [eval_wish] evaluates a given script in wish and returns, as a
+//!    [Result], its complete result or the error reported by Tcl.
 //!
 //! For example, label's
 //! [takefocus](https://www.tcl-lang.org/man/tcl8.6/TkCmd/ttk_widget.htm#M-takefocus)
@@ -83,10 +92,14 @@
 //!
 
 use std::collections::HashMap;
+use std::error;
+use std::fmt;
 use std::io::{Read, Write};
+use std::ops::Range;
 use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
-use std::sync::{Mutex, OnceLock};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
 
 use super::font;
@@ -96,10 +109,25 @@ use super::widget;
 /// Reports an error in interacting with the Tk program.
 #[derive(Debug)]
 pub struct TkError {
-    #[allow(dead_code)]
     message: String,
 }
 
+impl fmt::Display for TkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &self.message)
+    }
+}
+
+impl error::Error for TkError {}
+
+impl TkError {
+    pub(super) fn new(message: &str) -> TkError {
+        TkError {
+            message: String::from(message),
+        }
+    }
+}
+
 static TRACE_WISH: OnceLock<bool> = OnceLock::new();
 fn tracing() -> bool {
     *TRACE_WISH.get().unwrap_or(&false)
@@ -108,6 +136,11 @@ fn tracing() -> bool {
 static mut WISH: OnceLock<process::Child> = OnceLock::new();
 static mut OUTPUT: OnceLock<process::ChildStdout> = OnceLock::new();
 static mut SENDER: OnceLock<mpsc::Sender<String>> = OnceLock::new();
+// Set once wish has exited, to end any running event loops.
+static FINISHED: AtomicBool = AtomicBool::new(false);
+// Output read from wish but not yet handled: shared, so messages read by a
+// nested event loop which has finished are handled by the loop around it.
+static PENDING: Mutex<Vec<u8>> = Mutex::new(Vec::new());
 
 // Kills the wish process - should be called to exit
 pub(super) fn kill_wish() {
@@ -127,9 +160,14 @@ pub fn tell_wish(msg: &str) {
     if tracing() {
         println!("wish: {}", msg);
     }
+    // -- one message per send, so commands from different threads are not
+    // interleaved
     unsafe {
-        SENDER.get_mut().unwrap().send(String::from(msg)).unwrap();
-        SENDER.get_mut().unwrap().send(String::from("\n")).unwrap();
+        (*std::ptr::addr_of!(SENDER))
+            .get()
+            .unwrap()
+            .send(format!("{}\n", msg))
+            .unwrap();
     }
 }
 
@@ -139,21 +177,124 @@ pub fn tell_wish(msg: &str) {
 /// Use with caution: the message must be valid tcl.
 ///
 pub fn ask_wish(msg: &str) -> String {
-    tell_wish(msg);
+    // -- the output is marked, to tell it from events reported meanwhile
+    let msg = format!(
+        "puts rish_reply_start ; catch {{{}}} ; puts rish_reply_end ; flush stdout",
+        msg
+    );
+    tell_wish(&msg);
+
+    match read_reply(ask_reply) {
+        Some((_, result)) => {
+            let result = String::from_utf8_lossy(&result).trim().to_string();
+            if tracing() {
+                println!("---: {:?}", result);
+            }
+            result
+        }
+        None => panic!("Eval-wish failed to get a result"),
+    }
+}
 
-    unsafe {
-        let mut input = [32; 10000]; // TODO - long inputs can get split?
-        if OUTPUT.get_mut().unwrap().read(&mut input).is_ok() {
-            if let Ok(input) = String::from_utf8(input.to_vec()) {
-                if tracing() {
-                    println!("---: {:?}", input.trim());
-                }
-                return input.trim().to_string();
+/// Evaluates a tcl script in wish, returning its result, or an error
+/// holding the message reported by Tcl.
+///
+/// Unlike [ask_wish], the script must not output its own result with
+/// `puts`: the result is sent back by rish, and is read in full, however
+/// long it is and even if it spans several lines.
+///
+pub fn eval_wish(script: &str) -> Result<String, TkError> {
+    let msg = format!("rish_eval {{{}}}", script);
+    tell_wish(&msg);
+
+    // -- the reply is a header line "rish_result ok|error LENGTH" followed
+    // by LENGTH bytes of result
+    let (header, result) = read_reply(eval_reply).ok_or_else(|| TkError {
+        message: String::from("Eval-wish failed to get a result"),
+    })?;
+    let result = String::from_utf8_lossy(&result).to_string();
+    if tracing() {
+        println!("---: {:?}", result);
+    }
+    if header.split_whitespace().nth(1) == Some("ok") {
+        Ok(result)
+    } else {
+        Err(TkError { message: result })
+    }
+}
+
+// Reads from wish until `reply` finds a complete reply in the output read,
+// giving the range of the whole reply and of its content. The reply is
+// removed, and returned as its header text and content: any other output,
+// before or after it, is left in PENDING to be handled by the event loop.
+// Returns None if wish's output closes first.
+fn read_reply(
+    reply: impl Fn(&[u8]) -> Option<(Range<usize>, Range<usize>)>,
+) -> Option<(String, Vec<u8>)> {
+    loop {
+        {
+            let mut pending = PENDING.lock().unwrap();
+            if let Some((whole, content)) = reply(&pending) {
+                let header = String::from_utf8_lossy(&pending[whole.start..content.start])
+                    .trim()
+                    .to_string();
+                let result = pending[content].to_vec();
+                pending.drain(whole);
+                return Some((header, result));
             }
         }
+        if !read_output() {
+            return None;
+        }
     }
+}
+
+// Reads the next output from wish into PENDING, returning false if wish's
+// output has closed.
+fn read_output() -> bool {
+    let mut input = [0; 10000];
+    let count = unsafe {
+        (*std::ptr::addr_of_mut!(OUTPUT))
+            .get_mut()
+            .unwrap()
+            .read(&mut input)
+    };
+    match count {
+        Ok(0) | Err(_) => false,
+        Ok(count) => {
+            PENDING.lock().unwrap().extend_from_slice(&input[..count]);
+            true
+        }
+    }
+}
+
+// Returns the position of the first line in output, at or after from,
+// which starts with the marker.
+fn find_line(output: &[u8], marker: &[u8], from: usize) -> Option<usize> {
+    (from..output.len())
+        .filter(|start| *start == 0 || output[start - 1] == b'\n')
+        .find(|start| output[*start..].starts_with(marker))
+}
 
-    panic!("Eval-wish failed to get a result");
+// Finds the output between the markers written by ask_wish.
+fn ask_reply(output: &[u8]) -> Option<(Range<usize>, Range<usize>)> {
+    const START: &[u8] = b"rish_reply_start\n";
+    const END: &[u8] = b"rish_reply_end\n";
+    let start = find_line(output, START, 0)?;
+    let end = find_line(output, END, start + START.len())?;
+    Some((start..end + END.len(), start + START.len()..end))
+}
+
+// Finds the reply written by rish_eval: see eval_wish.
+fn eval_reply(output: &[u8]) -> Option<(Range<usize>, Range<usize>)> {
+    let start = find_line(output, b"rish_result ", 0)?;
+    let end = start + output[start..].iter().position(|&c| c == b'\n')? + 1;
+    let header = String::from_utf8_lossy(&output[start..end]).to_string();
+    let length = header.split_whitespace().nth(2)?.parse::<usize>().ok()?;
+    if output.len() < end + length {
+        return None;
+    }
+    Some((start..end + length, end..end + length))
 }
 
 // -- Counter for making new ids
@@ -219,6 +360,10 @@ pub(super) fn add_callback0(wid: &str, callback: Callback0) {
         .insert(String::from(wid), callback);
 }
 
+pub(super) fn remove_callback0(wid: &str) {
+    static_callbacks0().lock().unwrap().remove(wid);
+}
+
 fn get_callback0(wid: &str) -> Option<Callback0> {
     if let Some((_, command)) = static_callbacks0().lock().unwrap().remove_entry(wid) {
         Some(command)
@@ -393,76 +538,174 @@ fn eval_callback1_font(wid: &str, value: font::TkFont) {
     } // TODO - error?
 }
 
+// Prefix of the bindtags used for a widget's own events, followed by the
+// event's name and the widget's id.
+pub(super) const WINDOW_TAG: &str = "rish_window_";
+
+// Separates the id of a callback's widget from the rest of its key.
+const KEY_SEPARATOR: char = '|';
+
+// Returns the key to store a callback for the given widget under: the
+// widget's id, a separator, then the given name, which must be unique for
+// the widget. Callbacks stored under the widget's id alone also belong to
+// it.
+pub(super) fn callback_key(wid: &str, name: &str) -> String {
+    format!("{}{}{}", wid, KEY_SEPARATOR, name)
+}
+
+// Removes the callbacks stored for the given widget and its descendants,
+// e.g. once they are destroyed.
+pub(super) fn remove_callbacks(wid: &str) {
+    static_callbacks0()
+        .lock()
+        .unwrap()
+        .retain(|key, _| !callback_of(key, wid));
+    static_callbacks1bool()
+        .lock()
+        .unwrap()
+        .retain(|key, _| !callback_of(key, wid));
+    static_callbacks1event()
+        .lock()
+        .unwrap()
+        .retain(|key, _| !callback_of(key, wid));
+    static_callbacks1float()
+        .lock()
+        .unwrap()
+        .retain(|key, _| !callback_of(key, wid));
+}
+
+// Whether the callback key belongs to the widget, or one of its
+// descendants: see callback_key.
+fn callback_of(key: &str, wid: &str) -> bool {
+    let owner = key.split(KEY_SEPARATOR).next().unwrap_or(key);
+    owner == wid || owner.starts_with(&format!("{}.", wid.trim_end_matches('.')))
+}
+
 /// Loops while GUI events occur
 pub fn mainloop() {
-    unsafe {
-        loop {
-            let mut input = [32; 10000];
-            if OUTPUT.get_mut().unwrap().read(&mut input).is_ok() {
-                if let Ok(input) = String::from_utf8(input.to_vec()) {
-                    if tracing() {
-                        println!("Callback: {:?}", &input.trim());
-                    }
+    run_events(|| false);
+}
 
-                    // here - do a match or similar on what was read from wish
-                    if input.starts_with("clicked") {
-                        // -- callbacks
-                        if let Some(n) = input.find(['\n', '\r']) {
-                            let widget = &input[8..n];
-                            eval_callback0(widget);
-                        }
-                    } else if input.starts_with("cb1b") {
-                        // -- callback 1 with bool
-                        let parts: Vec<&str> = input.split('-').collect();
-                        let widget = parts[1].trim();
-                        let value = parts[2].trim();
-                        eval_callback1_bool(widget, value == "1");
-                    } else if input.starts_with("cb1e") {
-                        // -- callback 1 with event
-                        let parts: Vec<&str> = input.split(':').collect();
-                        let widget_pattern = parts[1].trim();
-                        let x = parts[2].parse::<i64>().unwrap_or(0);
-                        let y = parts[3].parse::<i64>().unwrap_or(0);
-                        let root_x = parts[4].parse::<i64>().unwrap_or(0);
-                        let root_y = parts[5].parse::<i64>().unwrap_or(0);
-                        let height = parts[6].parse::<i64>().unwrap_or(0);
-                        let width = parts[7].parse::<i64>().unwrap_or(0);
-                        let key_code = parts[8].parse::<u64>().unwrap_or(0);
-                        let key_symbol = parts[9].parse::<String>().unwrap_or_default();
-                        let mouse_button = parts[10].parse::<u64>().unwrap_or(0);
-                        let event = widget::TkEvent {
-                            x,
-                            y,
-                            root_x,
-                            root_y,
-                            height,
-                            width,
-                            key_code,
-                            key_symbol,
-                            mouse_button,
-                        };
-                        eval_callback1_event(widget_pattern, event);
-                    } else if input.starts_with("cb1f") {
-                        // -- callback 1 with float
-                        let parts: Vec<&str> = input.split('-').collect();
-                        let widget = parts[1].trim();
-                        let value = parts[2].trim().parse::<f64>().unwrap_or(0.0);
-                        eval_callback1_float(widget, value);
-                    } else if let Some(font) = input.strip_prefix("font") {
-                        // -- callback 1 with font
-                        let font = font.trim();
-                        if let Ok(font) = font.parse::<font::TkFont>() {
-                            eval_callback1_font("font", font);
-                        }
-                    } else if input.starts_with("exit") {
-                        // -- wish has exited
-                        kill_wish();
-                        return; // exit loop and program
-                    }
+// Reads and handles messages from wish until `done` returns true, returning
+// false instead if wish exits. Called from a callback, this runs a nested
+// event loop, e.g. while a modal dialog is open.
+pub(super) fn run_events(done: impl Fn() -> bool) -> bool {
+    while !done() {
+        if FINISHED.load(Ordering::SeqCst) {
+            return false;
+        }
+        match next_message() {
+            Some(message) => {
+                if !handle_message(&message) {
+                    return false; // exit loop and program
                 }
             }
+            None => {
+                // -- wish's output has closed
+                FINISHED.store(true, Ordering::SeqCst);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+// Returns the next line of output from wish, reading more when no complete
+// line is pending, or None if wish's output has closed.
+fn next_message() -> Option<String> {
+    loop {
+        if let Some(line) = take_line(&mut PENDING.lock().unwrap()) {
+            return Some(line);
+        }
+        if !read_output() {
+            return None;
+        }
+    }
+}
+
+// Removes and returns the first complete line from the output, leaving any
+// partial line for the next read.
+fn take_line(pending: &mut Vec<u8>) -> Option<String> {
+    let n = pending.iter().position(|&c| c == b'\n')?;
+    let line: Vec<u8> = pending.drain(..=n).collect();
+    Some(String::from_utf8_lossy(&line).trim_end().to_string())
+}
+
+// Handles a single message from wish, returning false if wish has exited.
+fn handle_message(input: &str) -> bool {
+    if tracing() {
+        println!("Callback: {:?}", input);
+    }
+
+    // here - do a match or similar on what was read from wish
+    if let Some(widget) = input.strip_prefix("clicked-") {
+        // -- callbacks
+        eval_callback0(widget);
+    } else if input.starts_with("cb1b") {
+        // -- callback 1 with bool
+        let parts: Vec<&str> = input.split('-').collect();
+        let widget = parts[1].trim();
+        let value = parts[2].trim();
+        eval_callback1_bool(widget, value == "1");
+    } else if input.starts_with("cb1e") {
+        // -- callback 1 with event
+        let parts: Vec<&str> = input.split(':').collect();
+        let widget_pattern = parts[1].trim();
+        let x = parts[2].parse::<i64>().unwrap_or(0);
+        let y = parts[3].parse::<i64>().unwrap_or(0);
+        let root_x = parts[4].parse::<i64>().unwrap_or(0);
+        let root_y = parts[5].parse::<i64>().unwrap_or(0);
+        let height = parts[6].parse::<i64>().unwrap_or(0);
+        let width = parts[7].parse::<i64>().unwrap_or(0);
+        let key_code = parts[8].parse::<u64>().unwrap_or(0);
+        let key_symbol = parts[9].parse::<String>().unwrap_or_default();
+        let mouse_button = parts[10].parse::<u64>().unwrap_or(0);
+        let event = widget::TkEvent {
+            x,
+            y,
+            root_x,
+            root_y,
+            height,
+            width,
+            key_code,
+            key_symbol,
+            mouse_button,
+        };
+        eval_callback1_event(widget_pattern, event);
+    } else if input.starts_with("cb1f") {
+        // -- callback 1 with float
+        let parts: Vec<&str> = input.split('-').collect();
+        let widget = parts[1].trim();
+        let value = parts[2].trim().parse::<f64>().unwrap_or(0.0);
+        eval_callback1_float(widget, value);
+    } else if let Some(font) = input.strip_prefix("font") {
+        // -- callback 1 with font
+        let font = font.trim();
+        if let Ok(font) = font.parse::<font::TkFont>() {
+            eval_callback1_font("font", font);
         }
+    } else if input.starts_with("exit") {
+        // -- wish has exited
+        kill_wish();
+        FINISHED.store(true, Ordering::SeqCst);
+        return false;
     }
+    true
+}
+
+// Sends a script which arranges for wish to report the callback0 key, and
+// handles events until it does: returns false if wish exits first.
+pub(super) fn wait_for(key: &str, script: &str) -> bool {
+    let done = Arc::new(AtomicBool::new(false));
+    let flag = done.clone();
+    add_callback0(
+        key,
+        mk_callback0(move || flag.store(true, Ordering::SeqCst)),
+    );
+    tell_wish(script);
+    let result = run_events(|| done.load(Ordering::SeqCst));
+    remove_callback0(key);
+    result
 }
 
 /// Creates a connection with the "wish" program.
@@ -522,6 +765,14 @@ fn start_tk_connection(wish: &str) -> Result<toplevel::TkTopLevel, TkError> {
 
         // -- initial setup of Tcl/Tk environment
 
+        // commands are sent, and results read back, as utf-8 text with plain
+        // newlines: rish_eval counts the bytes of each result in this encoding
+        input
+            .write_all(
+                b"fconfigure stdin -encoding utf-8 ; fconfigure stdout -encoding utf-8 -translation lf\n",
+            )
+            .unwrap();
+
         // load the plotchart package - TODO: give some indication if this fails
         input.write_all(b"package require Plotchart\n").unwrap();
 
@@ -551,6 +802,294 @@ fn start_tk_connection(wish: &str) -> Result<toplevel::TkTopLevel, TkError> {
         }\n",
             )
             .unwrap();
+        // tcl function to return a script's result, or error, to eval_wish
+        input
+            .write_all(
+                b"proc rish_eval {script} {
+            set status [catch {uplevel #0 $script} result]
+            set length [string length [encoding convertto utf-8 $result]]
+            puts \"rish_result [expr {$status == 1 ? {error} : {ok}}] $length\"
+            puts -nonewline $result
+            flush stdout
+        }\n",
+            )
+            .unwrap();
+        // tcl functions to keep a canvas scroll region fitted to its items
+        input
+            .write_all(
+                b"proc rish_fit_scroll_region {c} {
+            if {[winfo exists $c]} {
+                $c configure -scrollregion [$c bbox all]
+            }
+        }\n",
+            )
+            .unwrap();
+        input
+            .write_all(
+                b"proc rish_canvas_changed {c cmd args} {
+            if {[lindex $cmd 1] in {create delete move moveto coords scale itemconfigure}} {
+                after cancel [list rish_fit_scroll_region $c]
+                after idle [list rish_fit_scroll_region $c]
+            }
+        }\n",
+            )
+            .unwrap();
+
+        // tcl functions to track the lines changed by edits to a text widget,
+        // reporting them once the edits are done
+        input
+            .write_all(
+                b"proc rish_text_enter {t cmd op} {
+            global rish_text_edit
+            switch -- [lindex $cmd 1] {
+                insert {
+                    set first [$t index [lindex $cmd 2]]
+                    set last $first
+                }
+                delete - replace {
+                    set first [$t index [lindex $cmd 2]]
+                    set last $first
+                    if {[llength $cmd] > 3} { set last [$t index [lindex $cmd 3]] }
+                }
+                edit {
+                    if {[lindex $cmd 2] ni {undo redo}} return
+                    set first 1.0
+                    set last [$t index end]
+                }
+                default return
+            }
+            set lines [lindex [split [$t index end] .] 0]
+            set rish_text_edit($t) [list [lindex [split $first .] 0] [lindex [split $last .] 0] $lines]
+        }\n",
+            )
+            .unwrap();
+        input
+            .write_all(
+                b"proc rish_text_leave {t cmd code result op} {
+            global rish_text_edit rish_text_dirty
+            if {![info exists rish_text_edit($t)]} return
+            lassign $rish_text_edit($t) first last lines
+            unset rish_text_edit($t)
+            set delta [expr {[lindex [split [$t index end] .] 0] - $lines}]
+            set last [expr {max($first, $last + $delta)}]
+            if {[info exists rish_text_dirty($t)]} {
+                lassign $rish_text_dirty($t) f l
+                if {$l >= $first} { set l [expr {max($first, $l + $delta)}] }
+                set rish_text_dirty($t) [list [expr {min($f, $first)}] [expr {max($l, $last)}]]
+            } else {
+                set rish_text_dirty($t) [list $first $last]
+                after idle [list rish_text_report $t]
+            }
+        }\n",
+            )
+            .unwrap();
+        input
+            .write_all(
+                b"proc rish_text_report {t} {
+            puts clicked-$t|highlight
+            flush stdout
+        }\n",
+            )
+            .unwrap();
+
+        // tcl functions to draw line numbers for the visible lines of a text
+        // widget in a canvas, redrawing after the text is scrolled, resized
+        // or edited
+        input
+            .write_all(
+                b"proc rish_gutter_update {t g} {
+            if {![winfo exists $t] || ![winfo exists $g]} return
+            $g delete all
+            set font [$t cget -font]
+            set last [lindex [split [$t index {end -1 chars}] .] 0]
+            $g configure -width [expr {[font measure $font $last] + 8}]
+            set i [$t index @0,0]
+            while {[llength [set info [$t dlineinfo $i]]] > 0} {
+                $g create text [expr {[winfo width $g] - 4}] [lindex $info 1] \\
+                    -anchor ne -font $font -fill gray40 -text [lindex [split $i .] 0]
+                set next [$t index \"$i +1 lines linestart\"]
+                if {$next eq $i || [$t compare $next >= end]} break
+                set i $next
+            }
+        }\n",
+            )
+            .unwrap();
+        input
+            .write_all(
+                b"proc rish_gutter_scroll {t g command args} {
+            if {$command ne {}} { uplevel #0 $command $args }
+            after cancel [list rish_gutter_update $t $g]
+            after idle [list rish_gutter_update $t $g]
+        }\n",
+            )
+            .unwrap();
+        input
+            .write_all(
+                b"proc rish_gutter_edit {t g cmd code result op} {
+            if {[lindex $cmd 1] in {insert delete replace edit}} {
+                rish_gutter_scroll $t $g {}
+            }
+        }\n",
+            )
+            .unwrap();
+
+        // tcl function to append lines to a log view, keeping the view at the
+        // bottom if it was there already
+        input
+            .write_all(
+                b"proc rish_log_append {t clear excess entries} {
+            if {![winfo exists $t]} return
+            set atend [expr {[lindex [$t yview] 1] >= 1.0}]
+            $t configure -state normal
+            if {$clear} { $t delete 1.0 end }
+            set args {}
+            foreach {line tag} $entries { lappend args $line\\n $tag }
+            if {[llength $args] > 0} { $t insert end {*}$args }
+            if {$excess > 0} { $t delete 1.0 [expr {$excess + 1}].0 }
+            $t configure -state disabled
+            if {$atend} { $t yview moveto 1.0 }
+        }\n",
+            )
+            .unwrap();
+
+        // tcl functions to sort a treeview's items by a column, reversing
+        // the order when the same column is sorted again, and marking the
+        // sorted column's heading with the direction
+        input
+            .write_all(
+                b"proc rish_treeview_sort {t column} {
+            global rish_treeview_sorted
+            set decreasing 0
+            if {[info exists rish_treeview_sorted($t)] &&
+                [lindex $rish_treeview_sorted($t) 0] eq $column} {
+                set decreasing [expr {![lindex $rish_treeview_sorted($t) 1]}]
+            }
+            set rish_treeview_sorted($t) [list $column $decreasing]
+            rish_treeview_sort_children $t {} $column $decreasing
+            if {$decreasing} { set arrow \\u25bc } else { set arrow \\u25b2 }
+            foreach c [concat #0 [$t cget -columns]] {
+                set text [regsub { [\\u25b2\\u25bc]$} [$t heading $c -text] {}]
+                if {$c eq $column} { append text { } $arrow }
+                $t heading $c -text $text
+            }
+        }\n",
+            )
+            .unwrap();
+        input
+            .write_all(
+                b"proc rish_treeview_sort_children {t parent column decreasing} {
+            set rows {}
+            set numeric 1
+            foreach item [$t children $parent] {
+                if {$column eq {#0}} {
+                    set value [$t item $item -text]
+                } else {
+                    set value [$t set $item $column]
+                }
+                if {![string is double -strict $value]} { set numeric 0 }
+                lappend rows [list $value $item]
+                rish_treeview_sort_children $t $item $column $decreasing
+            }
+            if {$numeric} { set options {-real} } else { set options {-dictionary} }
+            if {$decreasing} { lappend options -decreasing }
+            set index 0
+            foreach row [lsort -index 0 {*}$options $rows] {
+                $t move [lindex $row 1] $parent $index
+                incr index
+            }
+        }\n",
+            )
+            .unwrap();
+
+        // tcl function returning the number of rows which fit in a data
+        // table's treeview of the given height, measured from its first row
+        input
+            .write_all(
+                b"proc rish_table_page {t height} {
+            set box [$t bbox row0]
+            if {[llength $box] == 4} {
+                lassign $box x top width row
+            } else {
+                set row [ttk::style lookup Treeview -rowheight]
+                if {![string is integer -strict $row] || $row <= 0} {
+                    set row [expr {[font metrics TkDefaultFont -linespace] + 4}]
+                }
+                set top $row
+            }
+            return [expr {max(1, ($height - $top) / $row)}]
+        }\n",
+            )
+            .unwrap();
+
+        // tcl function to scroll a data table, reporting the new top row
+        // when it changes
+        input
+            .write_all(
+                b"proc rish_table_scroll {t key command number {what units}} {
+            global rish_table
+            set rows $rish_table($t,rows)
+            set page $rish_table($t,page)
+            set top $rish_table($t,top)
+            if {$command eq {moveto}} {
+                set top [expr {round($number * $rows)}]
+            } elseif {$what eq {pages}} {
+                set top [expr {$top + $number * $page}]
+            } else {
+                set top [expr {$top + $number}]
+            }
+            set top [expr {max(0, min($top, $rows - $page))}]
+            if {$top != $rish_table($t,top)} {
+                set rish_table($t,top) $top
+                puts cb1f-$key-$top
+                flush stdout
+            }
+        }\n",
+            )
+            .unwrap();
+
+        // tcl functions to report, as key, the first of the given events on
+        // a window, through a bindtag used by that window alone
+        input
+            .write_all(
+                b"proc rish_wait {w events key} {
+            foreach event $events { bind $key $event [list rish_wait_done $w $key] }
+            bindtags $w [linsert [bindtags $w] 0 $key]
+        }\n",
+            )
+            .unwrap();
+        input
+            .write_all(
+                b"proc rish_wait_done {w key} {
+            if {[winfo exists $w]} {
+                bindtags $w [lsearch -all -inline -not -exact [bindtags $w] $key]
+            }
+            puts clicked-$key
+            flush stdout
+        }\n",
+            )
+            .unwrap();
+        input
+            .write_all(
+                b"proc rish_wait_trace {key args} {
+            puts clicked-$key
+            flush stdout
+        }\n",
+            )
+            .unwrap();
+        // tcl function to grab input for a modal dialog, once it is shown
+        input
+            .write_all(
+                b"proc rish_modal_grab {w} {
+            if {![winfo exists $w]} return
+            if {[catch {grab set $w}]} {
+                after 10 [list rish_modal_grab $w]
+            } else {
+                raise $w
+                focus $w
+            }
+        }\n",
+            )
+            .unwrap();
 
         let (sender, receiver) = mpsc::channel();
         SENDER.set(sender).expect(&err_msg);
@@ -607,6 +1146,127 @@ pub(super) fn split_items(text: &str) -> Vec<String> {
     result
 }
 
+// Splits a tcl list into its elements, following the tcl rules for
+// braces, quotes and backslashes: unlike split_items, braces may nest and
+// elements may hold any characters.
+pub(super) fn split_list(text: &str) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    let mut chars = text.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(first) = chars.next() else {
+            break;
+        };
+
+        let mut element = String::new();
+        match first {
+            '{' => {
+                // -- braced: taken literally, up to the matching brace
+                let mut depth = 1;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            element.push(c);
+                            if let Some(escaped) = chars.next() {
+                                element.push(escaped);
+                            }
+                            continue;
+                        }
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    element.push(c);
+                }
+            }
+            '"' => {
+                // -- quoted: up to the closing quote, with backslash escapes
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => element.push(unescape(&mut chars)),
+                        _ => element.push(c),
+                    }
+                }
+            }
+            _ => {
+                // -- bare word: up to whitespace, with backslash escapes
+                let mut c = first;
+                loop {
+                    if c == '\\' {
+                        element.push(unescape(&mut chars));
+                    } else {
+                        element.push(c);
+                    }
+                    match chars.next_if(|c| !c.is_whitespace()) {
+                        Some(next) => c = next,
+                        None => break,
+                    }
+                }
+            }
+        }
+        result.push(element);
+    }
+
+    result
+}
+
+// Returns the character for a backslash sequence, the backslash having
+// already been read.
+fn unescape(chars: &mut std::iter::Peekable<std::str::Chars>) -> char {
+    match chars.next() {
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('u') => {
+            let mut code = String::new();
+            while code.len() < 4 {
+                match chars.next_if(|c| c.is_ascii_hexdigit()) {
+                    Some(c) => code.push(c),
+                    None => break,
+                }
+            }
+            u32::from_str_radix(&code, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .unwrap_or('u')
+        }
+        Some(c) => c,
+        None => '\\',
+    }
+}
+
+// Quotes text as a single tcl word, escaping every character that tcl
+// would otherwise treat specially. The result can safely be placed
+// anywhere in a command, including within braces.
+pub(super) fn tcl_quote(text: &str) -> String {
+    if text.is_empty() {
+        return String::from("{}");
+    }
+
+    let mut result = String::new();
+    for c in text.chars() {
+        match c {
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            '\r' => result.push_str("\\r"),
+            '\\' | '{' | '}' | '[' | ']' | '$' | '"' | ';' | '#' | ' ' => {
+                result.push('\\');
+                result.push(c);
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -647,4 +1307,94 @@ mod tests {
         assert_eq!("xy_z", result[1]);
         assert_eq!("another", result[2]);
     }
+
+    #[test]
+    fn split_list_nested() {
+        let result = split_list("a {b {c d} e} {} \"f g\" h\\ i");
+        assert_eq!(vec!["a", "b {c d} e", "", "f g", "h i"], result);
+    }
+
+    #[test]
+    fn split_list_escapes() {
+        let result = split_list("{x\\}y} a\\nb \"\\u00e9\\\"\"\n{line\nbreak}");
+        assert_eq!(vec!["x\\}y", "a\nb", "\u{e9}\"", "line\nbreak"], result);
+    }
+
+    #[test]
+    fn callback_keys() {
+        assert!(callback_of(".r1", ".r1"));
+        assert!(callback_of(&callback_key(".r1", "heading#0"), ".r1"));
+        assert!(callback_of(&callback_key(".r1.r2", "<Map>"), ".r1"));
+        assert!(callback_of(&callback_key(".r1", "destroy<Destroy>"), ".r1"));
+        assert!(callback_of(&callback_key(".r2", "x"), "."));
+        assert!(!callback_of(".r12", ".r1"));
+        assert!(!callback_of("waitwindow.r1", ".r1"));
+        assert!(!callback_of(&callback_key("all", "<Key>"), ".r1"));
+    }
+
+    #[test]
+    fn callback_keys_canvas_items() {
+        // -- binding on item 5 of canvas .r1, and on canvas .r15
+        let item = callback_key(".r1", "5<Button-1>");
+        let canvas = callback_key(".r15", "<Button-1>");
+        assert_ne!(item, canvas);
+        assert!(callback_of(&item, ".r1"));
+        assert!(!callback_of(&item, ".r15"));
+        assert!(callback_of(&canvas, ".r15"));
+        assert!(!callback_of(&canvas, ".r1"));
+    }
+
+    #[test]
+    fn take_lines() {
+        // -- two messages and part of a third in one read
+        let mut pending = b"clicked-.r1\ncb1b-.r2|select-1\r\ncb1f-.r3".to_vec();
+        assert_eq!(Some(String::from("clicked-.r1")), take_line(&mut pending));
+        assert_eq!(
+            Some(String::from("cb1b-.r2|select-1")),
+            take_line(&mut pending)
+        );
+        assert_eq!(None, take_line(&mut pending));
+        pending.extend_from_slice(b"-0.5\n");
+        assert_eq!(Some(String::from("cb1f-.r3-0.5")), take_line(&mut pending));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn ask_replies() {
+        let output = b"clicked-.r1\nrish_reply_start\n10 20\nrish_reply_end\nclicked-.r2\n";
+        let (whole, content) = ask_reply(output).unwrap();
+        assert_eq!(b"10 20\n", &output[content]);
+        let mut rest = output.to_vec();
+        rest.drain(whole);
+        assert_eq!(b"clicked-.r1\nclicked-.r2\n".to_vec(), rest);
+
+        let (_, content) = ask_reply(b"rish_reply_start\nrish_reply_end\n").unwrap();
+        assert!(content.is_empty());
+        assert_eq!(None, ask_reply(b"rish_reply_start\n10 20\n"));
+    }
+
+    #[test]
+    fn eval_replies() {
+        // -- events before and after the reply are left in the output
+        let output = b"clicked-.r1\nrish_result ok 7\na\nb {c}cb1f-.r2|page-5\n";
+        let (whole, content) = eval_reply(output).unwrap();
+        assert_eq!(b"a\nb {c}", &output[content]);
+        let mut rest = output.to_vec();
+        rest.drain(whole);
+        assert_eq!(b"clicked-.r1\ncb1f-.r2|page-5\n".to_vec(), rest);
+
+        // -- incomplete until all of the result has been read
+        assert_eq!(None, eval_reply(b"rish_result error 12\nnot yet"));
+        assert_eq!(None, eval_reply(b"clicked-rish_result ok 0\n"));
+        assert!(eval_reply(b"rish_result ok 0\n").is_some());
+    }
+
+    #[test]
+    fn tcl_quote_round_trip() {
+        for text in ["", "plain", "a {b c", "$x [y] \\ \"z\";\n\t#"] {
+            let quoted = tcl_quote(text);
+            assert_eq!(vec![text], split_list(&quoted));
+            assert!(!quoted.contains('\n'));
+        }
+    }
 }